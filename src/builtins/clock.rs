@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::sloth::structure::{ObjectBlueprint, StructSignature, SlothObject};
 use crate::{errors::Error, sloth::types::Type};
@@ -48,15 +49,15 @@ pub fn get_function(f_name: String) -> Box<dyn SlothFunction> {
             "now",
             Some("clock"),
             None,
-            Type::Object("Instant".to_string()),
+            Type::Object(StructSignature::new(Some("clock".to_string()), "Instant".to_string())),
             now
         ),
 
         "since" => BuiltInFunction::new(
             "since",
             Some("clock"),
-            Some(Type::Object("Instant".to_string())),
-            Type::Object("Duration".to_string()),
+            Some(Type::Object(StructSignature::new(Some("clock".to_string()), "Instant".to_string()))),
+            Type::Object(StructSignature::new(Some("clock".to_string()), "Duration".to_string())),
             since
         ),
 
@@ -126,7 +127,11 @@ impl ObjectBlueprint for DurationBlueprint {
     }
 
     fn get_signature(&self) -> StructSignature {
-        StructSignature::new(Some("file".to_string()), "Duration".to_string())
+        StructSignature::new(Some("clock".to_string()), "Duration".to_string())
+    }
+
+    fn is_public(&self) -> bool {
+        true
     }
 
     /// Duration as seconds (in f64)
@@ -177,6 +182,10 @@ impl SlothObject for Duration {
         Ok(Rc::new(RefCell::new(value)))
     }
 
+    fn is_field_public(&self, _: &String) -> bool {
+        true
+    }
+
     fn get_fields(&self) -> (Vec<String>, Vec<Rc<RefCell<Value>>>) {
         (Vec::new(), Vec::new())
     }
@@ -212,7 +221,11 @@ impl ObjectBlueprint for InstantBlueprint {
     }
 
     fn get_signature(&self) -> StructSignature {
-        StructSignature::new(Some("file".to_string()), "Instant".to_string())
+        StructSignature::new(Some("clock".to_string()), "Instant".to_string())
+    }
+
+    fn is_public(&self) -> bool {
+        true
     }
 
     fn build(&self, _: Vec<Rc<RefCell<Value>>>) -> Result<Box<dyn SlothObject>, String> {
@@ -231,7 +244,7 @@ pub struct Date {
 
 impl SlothObject for Date {
     fn get_signature(&self) -> StructSignature {
-        StructSignature::new(Some("file".to_string()), "Instant".to_string())
+        StructSignature::new(Some("clock".to_string()), "Instant".to_string())
     }
 
     fn get_blueprint(&self) -> Box<dyn ObjectBlueprint> {
@@ -248,6 +261,10 @@ impl SlothObject for Date {
         Ok(Rc::new(RefCell::new(value)))
     }
 
+    fn is_field_public(&self, _: &String) -> bool {
+        true
+    }
+
     fn get_fields(&self) -> (Vec<String>, Vec<Rc<RefCell<Value>>>) {
         (Vec::new(), Vec::new())
     }
@@ -296,13 +313,13 @@ fn since(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Er
             let mut any = reference.to_owned();
             let object = match any.as_any().downcast_ref::<Date>() {
                 Some(v) => v,
-                None => return Err(Error::new(ErrMsg::RustError("Called function 'since' on an object which is not a Instant".to_string()), None))
+                None => return Err(Error::new(ErrMsg::RustError("Called function 'since' on an object which is not a Instant".to_string()), None).with_code(ErrCode::S0901))
             };
 
             object.inner.elapsed()
         },
         _ => {
-            return Err(Error::new(ErrMsg::RustError("Called function 'since' on an object which is not a Instant".to_string()), None))
+            return Err(Error::new(ErrMsg::RustError("Called function 'since' on an object which is not a Instant".to_string()), None).with_code(ErrCode::S0901))
         }
     };
 
@@ -322,7 +339,7 @@ fn sleep(scope: Rc<RefCell<Scope>>, _: &mut SlothProgram) -> Result<(), Error> {
         Value::Number(x) => {
             if x < 0.0 {
                 let err_msg = format!("Cannot wait for a negative duration ({})", x);
-                Err(Error::new(ErrMsg::InvalidArguments(err_msg), None))
+                Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0702))
             }
             else {
                 // The sleep occurs here
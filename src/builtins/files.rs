@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::sloth::structure::{ObjectBlueprint};
 use crate::{errors::Error, sloth::types::Type};
@@ -96,15 +97,15 @@ fn load(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Err
         _ => panic!("'query_inputs' failed")
     };
 
-    let content = match fs::read_to_string(&path) {
+    let content = match fs::read_to_string(path.as_str()) {
         Ok(f) => f,
         Err(e) => {
             let err_msg = format!("Could not open file '{}': {}", path, e.to_string());
-            return Err(Error::new(ErrMsg::RuntimeError(err_msg), None))
+            return Err(Error::new(ErrMsg::RuntimeError(err_msg), None).with_code(ErrCode::S0704))
         },
     };
 
-    super::set_return(&scope, program, Value::String(content))
+    super::set_return(&scope, program, Value::from(content))
 }
 
 
@@ -125,11 +126,11 @@ fn save(scope: Rc<RefCell<Scope>>, _: &mut SlothProgram) -> Result<(), Error> {
         _ => panic!("'query_inputs' failed")
     };
 
-    match fs::write(&path, string) {
+    match fs::write(path.as_str(), string.as_str()) {
         Ok(()) => Ok(()),
         Err(e) => {
             let err_msg = format!("Could not save to file '{}': {}", path, e.to_string());
-            return Err(Error::new(ErrMsg::RuntimeError(err_msg), None))
+            return Err(Error::new(ErrMsg::RuntimeError(err_msg), None).with_code(ErrCode::S0704))
         },
     }
 }
\ No newline at end of file
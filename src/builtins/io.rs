@@ -1,5 +1,7 @@
 use crate::sloth::structure::{ObjectBlueprint};
-use crate::{errors::Error, sloth::types::Type};
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg};
+use crate::sloth::types::Type;
 use crate::sloth::function::SlothFunction;
 use crate::sloth::program::SlothProgram;
 use crate::sloth::scope::Scope;
@@ -14,6 +16,46 @@ use text_io::read;
 
 
 
+/// Destination of the text printed by the program
+pub type Output = Box<dyn FnMut(&str)>;
+
+/// Source of the lines read by the program, or an error message if it cannot read
+pub type Input = Box<dyn FnMut() -> Result<String, String>>;
+
+thread_local! {
+    // when set, the printed text is given to this function instead of the standard output
+    static OUTPUT: RefCell<Option<Output>> = RefCell::new(None);
+
+    // when set, the lines read are given by this function instead of the standard input
+    static INPUT: RefCell<Option<Input>> = RefCell::new(None);
+}
+
+
+/// Give the text printed by the program to the function instead of writing it to the standard output
+pub fn redirect_output(output: Output) {
+    OUTPUT.with(|o| *o.borrow_mut() = Some(output));
+}
+
+
+/// Read the lines from the function instead of the standard input
+pub fn redirect_input(input: Input) {
+    INPUT.with(|i| *i.borrow_mut() = Some(input));
+}
+
+
+fn write_output(text: &str) {
+    OUTPUT.with(|o| match o.borrow_mut().as_mut() {
+        Some(output) => output(text),
+        None => {
+            print!("{}", text);
+            io::stdout().flush().unwrap();
+        }
+    })
+}
+
+
+
+
 pub const BUILTINS: [&str; 2] = [
     "print",
     "read",
@@ -103,8 +145,7 @@ fn print(scope: Rc<RefCell<Scope>>, _: &mut SlothProgram) -> Result<(), Error> {
     for (_, v) in inputs.iter().enumerate() {
         text += &format!("{}", v.borrow()).replace("\\n", "\n");
     }
-    print!("{}", text);
-    std::io::stdout().flush().unwrap();
+    write_output(&text);
     Ok(())
 }
 
@@ -120,12 +161,16 @@ fn read(scope: Rc<RefCell<Scope>>, p: &mut SlothProgram) -> Result<(), Error> {
     for (_, v) in inputs.iter().enumerate() {
         text += &format!("{}", v.borrow()).replace("\\n", "\n");
     }
-    print!("{}", text);
-
-    io::stdout().flush().unwrap();
-
-    let console_input: String = read!("{}\n");
-    let return_value = Value::String(console_input);
+    write_output(&text);
+
+    let console_input = INPUT.with(|i| match i.borrow_mut().as_mut() {
+        Some(input) => input(),
+        None => Ok(read!("{}\n"))
+    });
+    let return_value = match console_input {
+        Ok(s) => Value::from(s),
+        Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), None).with_code(ErrCode::S0705))
+    };
 
     super::set_return(&scope, p, return_value)
 }
\ No newline at end of file
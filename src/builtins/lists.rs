@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::sloth::structure::ObjectBlueprint;
 use crate::{errors::Error, sloth::types::Type};
@@ -125,11 +126,15 @@ pub fn get_struct(s_name: String) -> (Box<dyn ObjectBlueprint>, Vec<String>) {
 
 
 fn set(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error> {
-    let list_self = super::get_self(&scope, program)?;
     let inputs = super::query_inputs(&scope, vec![Type::Number, Type::Any], "set")?;
+    let self_ref = super::get_self_ref(&scope, program)?;
 
-    // get the list value
-    let (list_type, list_vec) = match list_self {
+    // the element is replaced in place: the elements are only copied if the list is shared with another value
+    let mut list_self = match self_ref.try_borrow_mut() {
+        Ok(v) => v,
+        Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
+    };
+    let (list_type, list_vec) = match &mut *list_self {
         Value::List(t, v) => (t, v),
         _ => panic!("Called 'set' on a value which is not a list")
     };
@@ -138,27 +143,14 @@ fn set(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Erro
 
 
     // the new value must be the same type as list_type
-    let new_value = {
-            if inputs[1].get_type() == list_type {&inputs[1]}
-            else {
-                let err_msg = format!("Tried to set an element of type '{}' in a list of type '{}'", inputs[1].get_type(), list_type);
-                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
-            }
-    };
-
-
-
-    // Try to set the value
-    let element_ref = list_vec[index].clone();
-    let res = match element_ref.try_borrow_mut() {
-        Ok(mut borrow) => {
-            *borrow = new_value.clone();
-            Ok(())
-        },
-        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None))
-    };
+    let new_value = inputs[1].clone();
+    if new_value.get_type() != *list_type {
+        let err_msg = format!("Tried to set an element of type '{}' in a list of type '{}'", new_value.get_type(), list_type);
+        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0502));
+    }
 
-    res
+    Rc::make_mut(list_vec)[index] = Rc::new(RefCell::new(new_value));
+    Ok(())
 }
 
 
@@ -211,32 +203,28 @@ fn get(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Erro
 
 
 fn push(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error> {
-    // Operations on list can't be made with references (due to them being Enums)
-    // so we take its inner values (type and vec), them create a new Value::List that we place at the same reference pointer
-
-    let list_self = super::get_self(&scope, program)?;
     let inputs = super::query_inputs(&scope, vec![Type::Any], "push")?;
+    let self_ref = super::get_self_ref(&scope, program)?;
 
-
-    // get the list elements as owned, in order to build a new one
-    let (list_type, mut list_vec) = match list_self {
-        Value::List(t, v) => (t, v.iter().map(|rc| rc.borrow().to_owned()).collect::<Vec<Value>>()),
+    // the list is modified in place: its elements are only copied if it is shared with another value
+    let mut list_self = match self_ref.try_borrow_mut() {
+        Ok(v) => v,
+        Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
+    };
+    let (list_type, list_vec) = match &mut *list_self {
+        Value::List(t, v) => (t, v),
         _ => panic!("Called 'push' on a value which is not a list")
     };
 
     // the pushed_value value must be the same type as list_type
     let pushed_value = inputs[0].clone();
-    if pushed_value.get_type() != list_type {
+    if pushed_value.get_type() != *list_type {
         let err_msg = format!("Tried to push a value of type '{}' to a list of type '{}'", pushed_value.get_type(), list_type);
-        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0502));
     }
 
-    list_vec.push(pushed_value);
-    let list_vec_ref = list_vec.iter().map(|v| Rc::new(RefCell::new(v.clone()))).collect::<Vec<Rc<RefCell<Value>>>>();
-    let new_list = Value::List(list_type, list_vec_ref);
-
-    // Modify the owner value
-    super::set_self(&scope, program, new_list)
+    Rc::make_mut(list_vec).push(Rc::new(RefCell::new(pushed_value)));
+    Ok(())
 }
 
 
@@ -258,26 +246,28 @@ fn push(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Err
 
 
 fn pull(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error> {
-    let list_self = super::get_self(&scope, program)?;
     let inputs = super::query_inputs(&scope, vec![Type::Number], "pull")?;
-
-    // get the list value
-    let (list_type, mut list_vec) = match list_self {
-        Value::List(t, v) => (t, v.iter().map(|rc| rc.borrow().to_owned()).collect::<Vec<Value>>()),
-        _ => panic!("Called 'pull' on a value which is not a list")
+    let self_ref = super::get_self_ref(&scope, program)?;
+
+    // the list is modified in place: its elements are only copied if it is shared with another value
+    let pulled = {
+        let mut list_self = match self_ref.try_borrow_mut() {
+            Ok(v) => v,
+            Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
+        };
+        let list_vec = match &mut *list_self {
+            Value::List(_, v) => v,
+            _ => panic!("Called 'pull' on a value which is not a list")
+        };
+
+        // index of the value to pull
+        let idx = super::expect_natural(&inputs[0], Some((list_vec.len(), "list length")), 0)?;
+
+        Rc::make_mut(list_vec).remove(idx)
     };
 
-    // index of the value to pull
-    let idx = super::expect_natural(&inputs[0], Some((list_vec.len(), "list length")), 0)?;
-
-
-    let pulled_value = list_vec.remove(idx);
-    let list_vec_ref = list_vec.iter().map(|v| Rc::new(RefCell::new(v.clone()))).collect::<Vec<Rc<RefCell<Value>>>>();
-    let new_list = Value::List(list_type, list_vec_ref);
-
-
-    super::set_return(&scope, program, pulled_value)?;
-    super::set_self(&scope, program, new_list)
+    let pulled_value = pulled.borrow().to_owned();
+    super::set_return(&scope, program, pulled_value)
 }
 
 
@@ -312,10 +302,11 @@ fn contains(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(),
     let list_self = super::get_self(&scope, program)?;
     let inputs = super::query_inputs(&scope, vec![Type::Any], "contains")?;
 
-    let list_vec: Vec<Value> = match list_self {
-        Value::List(_, v) => v.iter().map(|x| x.borrow().to_owned()).collect(),
+    let list_vec = match list_self {
+        Value::List(_, v) => v,
         _ => panic!("Called 'set' on a value which is not a list")
     };
 
-    super::set_return(&scope, program, Value::Boolean(list_vec.contains(&inputs[0])))
+    let found = list_vec.iter().any(|x| *x.borrow() == inputs[0]);
+    super::set_return(&scope, program, Value::Boolean(found))
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::{errors::Error, sloth::types::Type};
 use crate::sloth::function::SlothFunction;
@@ -137,7 +138,7 @@ fn sqrt(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Err
         Value::Number(x) => {
             if x < 0.0 {
                 let err_msg = format!("Called sqrt on a negative number ({})", x);
-                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None))
+                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0702))
             }
             Value::Number(x.sqrt())
         },
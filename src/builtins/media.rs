@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::sloth::structure::{ObjectBlueprint, SlothObject, StructSignature};
 use crate::{errors::Error, sloth::types::Type};
@@ -61,7 +62,7 @@ pub fn get_function(f_name: String) -> Box<dyn SlothFunction> {
         "update" => BuiltInFunction::new(
             "update",
             Some("media"),
-            Some(Type::Object("Canvas".to_string())),
+            Some(Type::Object(StructSignature::new(Some("media".to_string()), "Canvas".to_string()))),
             Type::Number,
             update  
         ),
@@ -69,7 +70,7 @@ pub fn get_function(f_name: String) -> Box<dyn SlothFunction> {
         "set_pixel" => BuiltInFunction::new(
             "set_pixel",
             Some("media"),
-            Some(Type::Object("Canvas".to_string())),
+            Some(Type::Object(StructSignature::new(Some("media".to_string()), "Canvas".to_string()))),
             Type::Number,
             set_pixel
         ),
@@ -77,7 +78,7 @@ pub fn get_function(f_name: String) -> Box<dyn SlothFunction> {
         "set_rect" => BuiltInFunction::new(
             "set_rect",
             Some("media"),
-            Some(Type::Object("Canvas".to_string())),
+            Some(Type::Object(StructSignature::new(Some("media".to_string()), "Canvas".to_string()))),
             Type::Number,
             set_rect
         ),
@@ -145,6 +146,10 @@ impl ObjectBlueprint for CanvasBlueprint {
         StructSignature::new(Some("media".to_string()), "Canvas".to_string())
     }
 
+    fn is_public(&self) -> bool {
+        true
+    }
+
     fn build(&self, given_values: Vec<Rc<RefCell<Value>>>) -> Result<Box<dyn crate::sloth::structure::SlothObject>, String> {
         // 3 inputs: window name, window x and window y
 
@@ -204,6 +209,10 @@ impl SlothObject for Canvas {
         Err(format!("No fields in Canvas"))
     }
 
+    fn is_field_public(&self, _: &String) -> bool {
+        true
+    }
+
     fn get_fields(&self) -> (Vec<String>, Vec<Rc<RefCell<Value>>>) {
         (Vec::new(), Vec::new())
     }
@@ -233,7 +242,7 @@ fn update(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), E
 
     let canvas = match obj.as_any().downcast_ref::<Canvas>() {
         Some(v) => v,
-        None => return Err(Error::new(ErrMsg::RustError("here".to_string()), None))
+        None => return Err(Error::new(ErrMsg::RustError("here".to_string()), None).with_code(ErrCode::S0901))
     };
 
     let res = match canvas.inner.try_borrow_mut() {
@@ -241,7 +250,7 @@ fn update(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), E
             reference.present();
             Ok(())
         },
-        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None)),
+        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901)),
     };
     
     res
@@ -260,7 +269,7 @@ fn set_pixel(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<()
     };
     let canvas = match obj.as_any().downcast_ref::<Canvas>() {
         Some(v) => v,
-        None => return Err(Error::new(ErrMsg::RustError("here".to_string()), None))
+        None => return Err(Error::new(ErrMsg::RustError("here".to_string()), None).with_code(ErrCode::S0901))
     };
 
 
@@ -280,10 +289,10 @@ fn set_pixel(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<()
             reference.set_draw_color((r, g, b));
             match reference.draw_point((x, y)) {
                 Ok(()) => Ok(()),
-                Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None))
+                Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
             }
         },
-        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None)),
+        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901)),
     };
     
     res
@@ -309,7 +318,7 @@ fn set_rect(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(),
     };
     let canvas = match obj.as_any().downcast_ref::<Canvas>() {
         Some(v) => v,
-        None => return Err(Error::new(ErrMsg::RustError("here".to_string()), None))
+        None => return Err(Error::new(ErrMsg::RustError("here".to_string()), None).with_code(ErrCode::S0901))
     };
 
 
@@ -332,10 +341,10 @@ fn set_rect(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(),
             reference.set_draw_color((r, g, b));
             match reference.fill_rect(Rect::new(x, y, width, height)) {
                 Ok(()) => Ok(()),
-                Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None))
+                Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
             }
         },
-        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None)),
+        Err(e) => Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901)),
     };
     
     res
@@ -364,14 +373,14 @@ fn last_event(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(
         if SDL_CONTEXT.is_none() {
             SDL_CONTEXT = match sdl2::init() {
                 Ok(v) => Some(v),
-                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None))
+                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
             }
         };
 
         let ep = SDL_CONTEXT.as_ref().unwrap().event_pump();
         let mut event_pump = match ep {
             Ok(v) => v,
-            Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None))
+            Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
         };
 
 
@@ -383,7 +392,7 @@ fn last_event(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(
     };
 
 
-    super::set_return(&scope, program, Value::String(code))
+    super::set_return(&scope, program, Value::from(code))
 }
 
 
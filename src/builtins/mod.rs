@@ -8,8 +8,10 @@ use crate::sloth::program::SlothProgram;
 use crate::sloth::scope::Scope;
 use crate::sloth::types::Type;
 use crate::sloth::structure::ObjectBlueprint;
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
 use crate::sloth::value::Value;
+use crate::suggestions;
 pub mod io;
 pub mod numbers;
 pub mod strings;
@@ -48,6 +50,28 @@ pub const MODULES: [&str; 9] = [
 
 
 
+/// Return the names of the builtins of the given module, or None if the module does not exist
+pub fn get_module_builtins(module: &str) -> Option<&'static [&'static str]> {
+    let builtins: &[&str] = match module {
+        "io" => &io::BUILTINS,
+        "numbers" => &numbers::BUILTINS,
+        "strings" => &strings::BUILTINS,
+        "lists" => &lists::BUILTINS,
+        "maths" => &maths::BUILTINS,
+        "files" => &files::BUILTINS,
+        "clock" => &clock::BUILTINS,
+        "media" => &media::BUILTINS,
+        "random" => &random::BUILTINS,
+        _ => return None
+    };
+    Some(builtins)
+}
+
+
+
+
+
+
 /// Struct representing the import of a builtin.
 /// It contains the module being imported, and a list of builtins from this module
 /// or the whole module if the list is None
@@ -70,7 +94,7 @@ impl BuiltInImport {
     /// * `disabled` - list of builtin modules disabled (via a program argument, usually)
     pub fn is_valid(&self, disabled: &Vec<String>) -> Result<(), String> {
         if !MODULES.contains(&self.module.as_str()) {
-            return Err(format!("Built-in module '{}' does not exists", self.module))
+            return Err(format!("Built-in module '{}' does not exists{}", self.module, suggestions::did_you_mean(&self.module, MODULES)))
         }
         if disabled.contains(&self.module) {
             return Err(format!("Built-in module '{}' is disabled", self.module))
@@ -97,7 +121,8 @@ impl BuiltInImport {
                 // Check that each builtins requested is in the submodule
                 for import in v {
                     if !builtins.contains(&import.as_str()) {
-                        return Err(format!("Built-in '{}' does not exists in the module '{}'", import, self.module))
+                        let suggestion = suggestions::did_you_mean(import, builtins.iter().copied());
+                        return Err(format!("Built-in '{}' does not exists in the module '{}'{}", import, self.module, suggestion))
                     }
                 }
 
@@ -256,6 +281,7 @@ impl SlothFunction for BuiltInFunction {
     fn get_module(&self) -> Option<String> {self.signature.module.clone()}
     fn get_name(&self) -> String {self.signature.name.clone()}
     fn get_output_type(&self) -> Type {self.signature.output_type.as_ref().unwrap().clone()}
+    fn is_public(&self) -> bool {true}
     fn get_input_types(&self) -> Option<Vec<Type>> {
         match &self.signature.input_types {
             None => None,
@@ -312,7 +338,7 @@ pub fn set_return(scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram, value:
                     *borrow = value;
                     Ok(())
                 },
-                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None))
+                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
             }
         },
         Err(e) => Err(e)
@@ -333,7 +359,7 @@ pub fn set_self(scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram, value: V
                     *borrow = value;
                     Ok(())
                 },
-                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None))
+                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None).with_code(ErrCode::S0901))
             }
         },
         Err(e) => Err(e)
@@ -353,7 +379,7 @@ pub fn query_inputs(scope: &Rc<RefCell<Scope>>, expected: Vec<Type>, func_name:
             1 => format!("Function '{}' requires 1 argument, but was given {}", func_name, inputs.len()),
             x => format!("Function '{}' requires {} argument(s), but was given {}", func_name, expected.len(), x)
         };
-        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
     }
 
     let mut res = Vec::new();
@@ -363,7 +389,7 @@ pub fn query_inputs(scope: &Rc<RefCell<Scope>>, expected: Vec<Type>, func_name:
         let brrw = given.borrow();
         if brrw.get_type() != expected {
             let err_msg = format!("Argument {} of function '{}' must be of type '{}', but was given a value of type '{}'", i, func_name, expected, brrw.get_type());
-            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
         }
         else {
             res.push(brrw.to_owned())
@@ -399,7 +425,7 @@ pub fn expect_natural(value: &Value, limit: Option<(usize, &str)>, arg_pos: usiz
 
     match res {
         Ok(u) => Ok(u),
-        Err(e) => Err(Error::new(ErrMsg::InvalidArguments(e), None))
+        Err(e) => Err(Error::new(ErrMsg::InvalidArguments(e), None).with_code(ErrCode::S0701))
     }
 }
 
@@ -407,10 +433,17 @@ pub fn expect_natural(value: &Value, limit: Option<(usize, &str)>, arg_pos: usiz
 
 /// Return the value stored by the method's caller
 pub fn get_self(scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<Value, Error> {
-    let value = match scope.borrow().get_variable("@self".to_string(), program) {
+    let value = match get_self_ref(scope, program) {
         Ok(v) => v.borrow().to_owned(),
         Err(e) => return Err(e),
     };
 
     Ok(value)
+}
+
+
+
+/// Return the reference to the value stored by the method's caller, to modify it in place
+pub fn get_self_ref(scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<Rc<RefCell<Value>>, Error> {
+    scope.borrow().get_variable("@self".to_string(), program)
 }
\ No newline at end of file
@@ -113,7 +113,7 @@ fn to_string(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<()
     let value = super::get_self(&scope, program)?;
 
     let result = match value {
-        Value::Number(x) => Value::String(x.to_string()),
+        Value::Number(x) => Value::from(x.to_string()),
         _ => panic!("Implementation of method 'to_string' for type 'num' was called on a value of another type")
     };
 
@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::sloth::structure::ObjectBlueprint;
 use crate::{errors::Error, sloth::types::Type};
@@ -91,7 +92,7 @@ fn range(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Er
 
     if max - min < 0.0 {
         let err_msg = format!("The first value (min) should be lower than the second value (max). Given {} and {}", min, max);
-        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None))
+        return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0702))
     }
     if min == max {
         return super::set_return(&scope, program, Value::Number(*min))
@@ -1,3 +1,4 @@
+use crate::codes::ErrCode;
 use crate::errors::ErrMsg;
 use crate::sloth::structure::ObjectBlueprint;
 use crate::{errors::Error, sloth::types::Type};
@@ -158,7 +159,7 @@ pub fn expect_positive_index(value: Option<Value>, limit: Option<usize>) -> Resu
 
     match res {
         Ok(u) => Ok(u),
-        Err(e) => Err(Error::new(ErrMsg::InvalidArguments(e), None))
+        Err(e) => Err(Error::new(ErrMsg::InvalidArguments(e), None).with_code(ErrCode::S0506))
     }
 }
 
@@ -185,7 +186,7 @@ fn to_num(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), E
                 Ok(v) => Value::Number(v),
                 Err(_) => {
                     let err_msg = format!("Cannot parse string \"{}\" into a Number", x);
-                    return Err(Error::new(ErrMsg::RuntimeError(err_msg), None));
+                    return Err(Error::new(ErrMsg::RuntimeError(err_msg), None).with_code(ErrCode::S0703));
                 }
             }
         },
@@ -241,7 +242,7 @@ fn insert(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), E
         Value::String(v) => v,
         _ => panic!()
     };
-    string.insert_str(idx, insertion);
+    Rc::make_mut(&mut string).insert_str(idx, insertion);
     
 
     super::set_self(&scope, program, Value::String(string))
@@ -267,7 +268,7 @@ fn push(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Err
     };
     
 
-    string.push_str(&insert_value);
+    Rc::make_mut(&mut string).push_str(insert_value);
     
     // try to edit owner value
     super::set_self(&scope, program, Value::String(string))
@@ -290,7 +291,7 @@ fn remove(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), E
     };
 
     let idx = super::expect_natural(&inputs[0], Some((string.len(), "string length")), 0)?;
-    string.remove(idx);
+    Rc::make_mut(&mut string).remove(idx);
     
     super::set_self(&scope, program, Value::String(string))
 }
@@ -309,7 +310,7 @@ fn split(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Er
 
         if inputs.len() != 1 {
             let err_msg = format!("Called function 'split' with {} argument(s), but the function requires 1 arguments", inputs.len());
-            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
         }
 
 
@@ -322,7 +323,7 @@ fn split(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Er
             Value::String(x) => x,
             v => {
                 let err_msg = format!("Argument 1 of function 'split' is of type string, given a value of type {}", v.get_type());
-                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
             }
         };
 
@@ -330,9 +331,9 @@ fn split(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Er
     };
     
 
-    let vec = string.split(&sep).map(|x| Rc::new(RefCell::new(Value::String(x.to_string())))).collect();
+    let vec = string.split(sep.as_str()).map(|x| Rc::new(RefCell::new(Value::from(x.to_string())))).collect();
 
-    super::set_return(&scope, program, Value::List(Type::String, vec))?;
+    super::set_return(&scope, program, Value::List(Type::String, Rc::new(vec)))?;
 
     Ok(())
 }
@@ -358,7 +359,7 @@ fn get(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Erro
 
         if inputs.len() != 1 {
             let err_msg = format!("Called function 'split' with {} argument(s), but the function requires 1 arguments", inputs.len());
-            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
         }
 
         let string = match owner_v {
@@ -371,7 +372,7 @@ fn get(scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Erro
         (string, idx)
     };
     
-    super::set_return(&scope, program, Value::String(string.char_indices().nth(idx).unwrap().1.to_string()))?;
+    super::set_return(&scope, program, Value::from(string.char_indices().nth(idx).unwrap().1.to_string()))?;
 
     Ok(())
 }
\ No newline at end of file
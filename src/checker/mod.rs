@@ -0,0 +1,27 @@
+//! Static checks run on the program once it is built, before its execution.
+
+use crate::errors::Error;
+use crate::sloth::program::SlothProgram;
+
+mod names;
+mod typing;
+
+
+
+
+/// Run every check on the program, returning the errors found, sorted by position
+pub fn check_program(program: &SlothProgram) -> Vec<Error> {
+    let mut errors = names::check_type_annotations(program);
+    errors.append(&mut names::check_statics(program));
+    errors.append(&mut typing::check_statics(program));
+
+    for function in program.get_custom_functions() {
+        errors.append(&mut names::check_function(function, program));
+        errors.append(&mut typing::check_function(function, program));
+    }
+
+    errors.sort_by_key(|e| {
+        e.position_trace.first().map(|p| (p.filename.clone(), p.line, p.first_column))
+    });
+    errors
+}
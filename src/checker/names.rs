@@ -0,0 +1,195 @@
+//! Resolution of the names used in the program: every function called, structure built or written as a type,
+//! and variable read must be defined somewhere.
+
+use std::collections::HashSet;
+
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg};
+use crate::position::Position;
+use crate::sloth::expression::Expression;
+use crate::sloth::function::CustomFunction;
+use crate::sloth::program::SlothProgram;
+use crate::sloth::statement::Statement;
+use crate::suggestions;
+
+
+
+
+struct NameResolver<'a> {
+    program: &'a SlothProgram,
+    module: Option<String>,         // module of the checked function
+    variables: HashSet<String>,     // variables assigned somewhere in the function
+    inputs_count: usize,            // number of @N variables
+    is_method: bool,                // whether @self is set
+    errors: Vec<Error>,
+}
+
+
+
+
+/// Check the names used in the given function, returning an error for each unknown name
+pub fn check_function(function: &CustomFunction, program: &SlothProgram) -> Vec<Error> {
+    let signature = &function.signature;
+
+    // a variable may be read before it is assigned in a loop, so every assigned variable is considered known
+    let mut variables = HashSet::new();
+    collect_assigned(&function.instructions, &mut variables);
+
+    let mut resolver = NameResolver {
+        program,
+        module: signature.module.clone(),
+        variables,
+        inputs_count: signature.input_types.as_ref().map_or(0, |t| t.len()),
+        is_method: signature.owner_type.is_some(),
+        errors: Vec::new(),
+    };
+
+    for statement in &function.instructions {
+        resolver.check_statement(statement);
+    }
+    resolver.errors
+}
+
+
+/// Check the names used in the expressions of the statics, which can only read other statics
+pub fn check_statics(program: &SlothProgram) -> Vec<Error> {
+    let mut resolver = NameResolver {
+        program,
+        module: None,
+        variables: HashSet::new(),
+        inputs_count: 0,
+        is_method: false,
+        errors: Vec::new(),
+    };
+
+    for name in program.get_static_names() {
+        if let Some(expr) = program.get_static_expr(&name) {
+            resolver.check_expression(&expr);
+        }
+    }
+    resolver.errors
+}
+
+
+/// Check that every structure written as a type exists and can be used where it is written
+pub fn check_type_annotations(program: &SlothProgram) -> Vec<Error> {
+    program.get_type_annotations().iter()
+        .filter_map(|(signature, module, p)| program.get_struct(signature, module).err().map(|e| name_error(ErrCode::S0405, e, p.clone())))
+        .collect()
+}
+
+
+
+
+/// Add the name of every variable assigned in the statements (including the ones in blocks)
+fn collect_assigned(statements: &[Statement], variables: &mut HashSet<String>) {
+    for statement in statements {
+        match statement {
+            Statement::Assignment(target, _, _) => {
+                if let Expression::VariableAccess(None, name, _) = target.as_ref() {
+                    variables.insert(name.clone());
+                }
+            },
+            Statement::If(_, block, _) | Statement::While(_, block, _) => collect_assigned(block, variables),
+            Statement::ExpressionCall(..) => (),
+        }
+    }
+}
+
+
+fn name_error(code: ErrCode, message: String, position: Position) -> Error {
+    Error::new(ErrMsg::NameError(message), Some(position)).with_code(code)
+}
+
+
+
+
+impl<'a> NameResolver<'a> {
+    fn check_statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Assignment(target, source, _) => {
+                self.check_expression(source);
+
+                // assigning a variable defines it, but the owner of a field or of a list element is read
+                match target.as_ref() {
+                    Expression::VariableAccess(None, ..) => (),
+                    e => self.check_expression(e),
+                }
+            },
+            Statement::ExpressionCall(expr, _) => self.check_expression(expr),
+            Statement::If(cond, block, _) | Statement::While(cond, block, _) => {
+                self.check_expression(cond);
+                for s in block {
+                    self.check_statement(s);
+                }
+            },
+        }
+    }
+
+
+    fn check_expression(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Literal(..) | Expression::MainCall(..) => (),
+
+            Expression::ListInit(exprs, _) => {
+                for e in exprs {self.check_expression(e)}
+            },
+
+            Expression::VariableAccess(None, name, p) => {
+                if let Err((code, e)) = self.check_variable(name) {
+                    self.errors.push(name_error(code, e, p.clone()));
+                }
+            },
+
+            Expression::VariableAccess(Some(owner), _, _) => self.check_expression(owner),
+
+            Expression::BracketAccess(owner, access, _) => {
+                self.check_expression(owner);
+                self.check_expression(access);
+            },
+
+            Expression::FunctionCall(owner, signature, arguments, p, _) => {
+                if let Some(o) = owner {self.check_expression(o)}
+                for e in arguments {self.check_expression(e)}
+
+                if let Err(e) = self.program.check_function_name(signature, &self.module) {
+                    self.errors.push(name_error(ErrCode::S0404, e, p.clone()));
+                }
+            },
+
+            Expression::ObjectConstruction(signature, arguments, p) => {
+                for e in arguments {self.check_expression(e)}
+
+                if let Err(e) = self.program.get_struct(signature, &self.module) {
+                    self.errors.push(name_error(ErrCode::S0405, e, p.clone()));
+                }
+            },
+        }
+    }
+
+
+    /// Return an error (and its code) if the variable can't be set when it is read
+    fn check_variable(&self, name: &String) -> Result<(), (ErrCode, String)> {
+        if self.variables.contains(name) || self.program.get_static_expr(name).is_some() {return Ok(())}
+
+        match name.strip_prefix('@') {
+            Some("return") => Ok(()),
+            Some("self") if self.is_method => Ok(()),
+            Some("self") => Err((ErrCode::S0402, "Variable '@self' is only defined in methods".to_string())),
+            Some(n) => match n.parse::<usize>() {
+                Ok(i) if i < self.inputs_count => Ok(()),
+                Ok(i) => Err((ErrCode::S0403, format!("Variable '@{}' is not defined: the function only takes {} argument(s)", i, self.inputs_count))),
+                Err(_) => Err((ErrCode::S0401, format!("Variable '{}' is not defined{}", name, self.variable_suggestion(name)))),
+            },
+            None => Err((ErrCode::S0401, format!("Variable '{}' is not defined{}", name, self.variable_suggestion(name)))),
+        }
+    }
+
+
+    /// Return a suggestion of a variable or static with a name close to the given one, or an empty string
+    fn variable_suggestion(&self, name: &str) -> String {
+        let statics = self.program.get_static_names();
+        let names = self.variables.iter().chain(statics.iter()).map(|n| n.as_str());
+        suggestions::did_you_mean(name, names)
+    }
+}
@@ -0,0 +1,357 @@
+//! Type inference of the expressions of the program, used to find type errors before running it.
+//! The inference is conservative: when the type of an expression can't be known, it is [Type::Any],
+//! which matches every other type, so only errors certain to happen at run time are reported.
+
+use std::collections::HashMap;
+
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg};
+use crate::position::Position;
+use crate::sloth::expression::Expression;
+use crate::sloth::function::{CustomFunction, FunctionCallSignature, FunctionSignature};
+use crate::sloth::program::SlothProgram;
+use crate::sloth::statement::Statement;
+use crate::sloth::structure::CustomDefinition;
+use crate::sloth::types::Type;
+use crate::suggestions;
+
+
+
+/// Type of each variable known at a point of a function
+type Variables = HashMap<String, Type>;
+
+
+
+
+struct TypeChecker<'a> {
+    program: &'a SlothProgram,
+    module: Option<String>,         // module of the checked function, used to find the structures
+    statics_stack: Vec<String>,     // statics being inferred, to stop on recursive definitions
+    errors: Vec<Error>,
+}
+
+
+
+
+/// Check the statements of the given function, returning the type errors found
+pub fn check_function(function: &CustomFunction, program: &SlothProgram) -> Vec<Error> {
+    let mut checker = TypeChecker::new(program, function.signature.module.clone());
+    let mut variables = initial_variables(&function.signature);
+
+    checker.check_statements(&function.instructions, &mut variables);
+    checker.errors
+}
+
+
+/// Check the expressions of the statics, returning the type errors found
+pub fn check_statics(program: &SlothProgram) -> Vec<Error> {
+    let mut checker = TypeChecker::new(program, None);
+
+    for name in program.get_static_names() {
+        if let Some(expr) = program.get_static_expr(&name) {
+            checker.statics_stack.push(name);
+            checker.infer(&expr, &mut Variables::new());
+            checker.statics_stack.pop();
+        }
+    }
+
+    checker.errors
+}
+
+
+
+
+/// Return the variables set when the function is called: the inputs, @return and @self
+fn initial_variables(signature: &FunctionSignature) -> Variables {
+    let mut variables = Variables::new();
+
+    if let Some(inputs) = &signature.input_types {
+        for (i, (t, _)) in inputs.iter().enumerate() {
+            variables.insert(format!("@{}", i), t.clone());
+        }
+    }
+    if let Some(t) = &signature.output_type {variables.insert("@return".to_string(), t.clone());}
+    if let Some(t) = &signature.owner_type {variables.insert("@self".to_string(), t.clone());}
+
+    variables
+}
+
+
+/// Return whether the type is not precise enough to be compared (any, list[any], etc.)
+fn is_unknown(t: &Type) -> bool {
+    match t {
+        Type::Any => true,
+        Type::List(inner) => is_unknown(inner),
+        _ => false
+    }
+}
+
+
+fn type_error(code: ErrCode, message: String, position: Position) -> Error {
+    Error::new(ErrMsg::TypeError(message), Some(position)).with_code(code)
+}
+
+
+
+
+impl<'a> TypeChecker<'a> {
+    fn new(program: &'a SlothProgram, module: Option<String>) -> TypeChecker<'a> {
+        TypeChecker {program, module, statics_stack: Vec::new(), errors: Vec::new()}
+    }
+
+
+    fn check_statements(&mut self, statements: &[Statement], variables: &mut Variables) {
+        for statement in statements {
+            self.check_statement(statement, variables);
+        }
+    }
+
+
+    /// Check the statements of an 'if' or 'while' block. As the block may not be executed,
+    /// what is learnt about the variables inside it is forgotten at its end
+    fn check_block(&mut self, statements: &[Statement], variables: &Variables) {
+        let mut inner_variables = variables.clone();
+        self.check_statements(statements, &mut inner_variables);
+    }
+
+
+    fn check_statement(&mut self, statement: &Statement, variables: &mut Variables) {
+        match statement {
+            Statement::Assignment(target, source, p) => {
+                let source_type = self.infer(source, variables);
+
+                // A variable which is not set yet (or set to a value of unknown type) takes the type of the source
+                if let Expression::VariableAccess(None, name, _) = target.as_ref() {
+                    let is_new = match variables.get(name) {
+                        Some(t) => is_unknown(t),
+                        None => self.program.get_static_expr(name).is_none(),
+                    };
+
+                    if is_new {
+                        variables.insert(name.clone(), source_type);
+                        return
+                    }
+                }
+
+                let target_type = self.infer(target, variables);
+                if source_type != target_type {
+                    let err_msg = format!("Expected a Value of type '{}', got type '{}' instead", target_type, source_type);
+                    self.errors.push(type_error(ErrCode::S0501, err_msg, p.clone()));
+                }
+            },
+
+            Statement::ExpressionCall(expr, _) => {
+                self.infer(expr, variables);
+            },
+
+            Statement::If(cond, statements, _) => {
+                let cond_type = self.infer(cond, variables);
+                if cond_type != Type::Boolean {
+                    let err_msg = format!("Expected boolean expression as 'if' condition, got type '{}'", cond_type);
+                    self.errors.push(type_error(ErrCode::S0503, err_msg, cond.get_pos()));
+                }
+                self.check_block(statements, variables);
+            },
+
+            Statement::While(cond, statements, _) => {
+                self.infer(cond, variables);
+                self.check_block(statements, variables);
+            },
+        }
+    }
+
+
+
+
+    /// Return the type of the value the expression evaluates to, reporting the type errors found in it
+    fn infer(&mut self, expr: &Expression, variables: &mut Variables) -> Type {
+        match expr {
+            Expression::Literal(v, _) => v.get_type(),
+
+            Expression::ListInit(exprs, _) => {
+                let types: Vec<Type> = exprs.iter().map(|e| self.infer(e, variables)).collect();
+
+                match types.first() {
+                    None => Type::List(Box::new(Type::Any)),
+                    Some(list_type) => {
+                        for (e, t) in exprs.iter().zip(&types).skip(1) {
+                            if t != list_type {
+                                let err_msg = format!("Created a list of type '{}' but this value is of type '{}'", list_type, t);
+                                self.errors.push(type_error(ErrCode::S0502, err_msg, e.get_pos()));
+                            }
+                        }
+                        Type::List(Box::new(list_type.clone()))
+                    }
+                }
+            },
+
+            Expression::VariableAccess(None, name, _) => {
+                match variables.get(name) {
+                    Some(t) => t.clone(),
+                    None => self.static_type(name),
+                }
+            },
+
+            Expression::VariableAccess(Some(owner), name, p) => {
+                let owner_type = self.infer(owner, variables);
+                self.field_type(&owner_type, name, p)
+            },
+
+            Expression::BracketAccess(owner, access, _) => {
+                let owner_type = self.infer(owner, variables);
+                self.infer(access, variables);
+
+                match owner_type {
+                    Type::List(inner) => *inner,
+                    _ => Type::Any
+                }
+            },
+
+            Expression::FunctionCall(owner, signature, arguments, p, _) => {
+                let input_types: Vec<Type> = arguments.iter().map(|e| self.infer(e, variables)).collect();
+                let owner_type = owner.as_ref().map(|o| self.infer(o, variables));
+                self.call_type(signature, owner_type, input_types, p)
+            },
+
+            Expression::ObjectConstruction(signature, arguments, p) => {
+                let given_types: Vec<Type> = arguments.iter().map(|e| self.infer(e, variables)).collect();
+
+                let blueprint = match self.program.get_struct(signature, &self.module) {
+                    Ok(b) => b,
+                    Err(_) => return Type::Any
+                };
+
+                if let Some(definition) = blueprint.as_ref().as_any_ref().downcast_ref::<CustomDefinition>() {
+                    if definition.fields.len() != given_types.len() {
+                        let err_msg = format!("Structure '{}' expects {} fields, but it has been given {} fields", signature.name, definition.fields.len(), given_types.len());
+                        self.errors.push(type_error(ErrCode::S0504, err_msg, p.clone()));
+                    }
+                    else {
+                        for ((field_name, field_type), (given, e)) in definition.fields.iter().zip(given_types.iter().zip(arguments)) {
+                            if given != field_type {
+                                let err_msg = format!("Field '{}' of structure '{}' is of type '{}', but it has been given a value of type '{}'", field_name, signature.name, field_type, given);
+                                self.errors.push(type_error(ErrCode::S0504, err_msg, e.get_pos()));
+                            }
+                        }
+                    }
+                }
+
+                Type::Object(blueprint.get_signature())
+            },
+
+            Expression::MainCall(_) => Type::Any,
+        }
+    }
+
+
+
+
+    /// Return the type of the given static. Errors in the static expression are reported by [check_statics]
+    fn static_type(&mut self, name: &String) -> Type {
+        let expr = match self.program.get_static_expr(name) {
+            Some(e) => e,
+            None => return Type::Any
+        };
+        if self.statics_stack.contains(name) {return Type::Any}
+
+        let nb_errors = self.errors.len();
+        self.statics_stack.push(name.clone());
+        let res = self.infer(&expr, &mut Variables::new());
+        self.statics_stack.pop();
+        self.errors.truncate(nb_errors);
+
+        res
+    }
+
+
+
+
+    /// Return the type of the field of a value of the given type
+    fn field_type(&mut self, owner_type: &Type, field_name: &String, position: &Position) -> Type {
+        match owner_type {
+            Type::Object(signature) => {
+                let blueprint = match self.program.get_struct(signature, &self.module) {
+                    Ok(b) => b,
+                    Err(_) => return Type::Any
+                };
+
+                // only the fields of the structures defined in Sloth are known
+                let definition = match blueprint.as_ref().as_any_ref().downcast_ref::<CustomDefinition>() {
+                    Some(d) => d,
+                    None => return Type::Any
+                };
+
+                match definition.fields.iter().find(|(n, _)| n == field_name) {
+                    Some((_, t)) => t.clone(),
+                    None => {
+                        let suggestion = suggestions::did_you_mean(field_name, definition.fields.iter().map(|(n, _)| n.as_str()));
+                        let err_msg = format!("Structure '{}' does not have a field named '{}'{}", signature.name, field_name, suggestion);
+                        self.errors.push(type_error(ErrCode::S0407, err_msg, position.clone()));
+                        Type::Any
+                    }
+                }
+            },
+
+            Type::List(inner) => {
+                match field_name.parse::<usize>() {
+                    Ok(_) => (**inner).clone(),
+                    Err(_) => Type::Any
+                }
+            },
+
+            Type::Any => Type::Any,
+
+            t => {
+                let err_msg = format!("Type '{}' doesn't have a field '{}'", t, field_name);
+                self.errors.push(type_error(ErrCode::S0407, err_msg, position.clone()));
+                Type::Any
+            }
+        }
+    }
+
+
+
+
+    /// Return the output type of the function call, reporting an error if no function accepts the owner or input types.
+    /// Calls to undefined functions are reported by the name resolution and return [Type::Any]
+    fn call_type(&mut self, signature: &FunctionCallSignature, owner_type: Option<Type>, input_types: Vec<Type>, position: &Position) -> Type {
+        let named: Vec<&FunctionSignature> = self.program.get_function_signatures().into_iter()
+            .filter(|k| k.name == signature.name)
+            .filter(|k| signature.module.is_none() || k.module.is_none() || k.module == signature.module)
+            .collect();
+
+        if named.is_empty() {return Type::Any}
+
+        let candidates: Vec<&FunctionSignature> = named.into_iter()
+            .filter(|k| k.owner_type == owner_type)
+            .collect();
+
+        if candidates.is_empty() {
+            if let Some(t) = owner_type.filter(|t| !is_unknown(t)) {
+                let err_msg = format!("Function '{}' is not defined for the type {}", signature.name, t);
+                self.errors.push(type_error(ErrCode::S0404, err_msg, position.clone()));
+            }
+            return Type::Any
+        }
+
+        let matching: Vec<&FunctionSignature> = candidates.iter().copied()
+            .filter(|k| match &k.input_types {
+                None => true,
+                Some(t) => t.iter().map(|(v, _)| v.clone()).collect::<Vec<Type>>() == input_types
+            })
+            .collect();
+
+        if matching.is_empty() {
+            let type_str = input_types.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", ");
+            let definitions = candidates.iter().map(|k| format!("'{}'", k.to_definition_string())).collect::<Vec<String>>().join(", ");
+            let err_msg = format!("Function '{}' is not defined for the following input types: {}\nNote: Available definitions: {}", signature.name, type_str, definitions);
+            self.errors.push(type_error(ErrCode::S0404, err_msg, position.clone()));
+            return Type::Any
+        }
+
+        // the output type is only known if every matching function returns the same type
+        let output_types: Vec<Type> = matching.iter().map(|k| k.output_type.clone().unwrap_or(Type::Any)).collect();
+        if output_types.iter().all(|t| t.strict_eq(&output_types[0])) {output_types[0].clone()}
+        else {Type::Any}
+    }
+}
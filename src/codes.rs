@@ -0,0 +1,123 @@
+//! Stable codes of the diagnostics, and their explanations printed by `sloth --explain`.
+//! A code never changes meaning once released: a new kind of diagnostic gets a new code.
+//! The explanations are stored in the `codes` directory and embedded in the binary.
+
+use crate::suggestions;
+
+
+
+/// Define the [ErrCode] enum, from the list of codes and their one-line summary.
+/// The explanation of the code XXXX is read from 'codes/XXXX.txt'
+macro_rules! error_codes {
+    ($($code:ident: $summary:literal,)*) => {
+        /// Code identifying a kind of diagnostic. The first two digits are the category:
+        /// 01 syntax, 02 definitions and imports, 03 files and manifests, 04 names, 05 types,
+        /// 06 entry point, 07 run time, 09 internal errors
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum ErrCode {
+            $($code,)*
+        }
+
+        impl ErrCode {
+            pub const ALL: &'static [ErrCode] = &[$(ErrCode::$code,)*];
+
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(ErrCode::$code => stringify!($code),)*
+                }
+            }
+
+            /// Return a one-line description of the diagnostic
+            pub fn get_summary(&self) -> &'static str {
+                match self {
+                    $(ErrCode::$code => $summary,)*
+                }
+            }
+
+            /// Return the long description of the diagnostic, with an example and how to fix it
+            pub fn get_explanation(&self) -> &'static str {
+                match self {
+                    $(ErrCode::$code => include_str!(concat!("codes/", stringify!($code), ".txt")),)*
+                }
+            }
+        }
+    };
+}
+
+
+error_codes! {
+    S0101: "Invalid token",
+    S0102: "Unexpected token",
+    S0103: "Unexpected end of file",
+    S0104: "Invalid attribute",
+
+    S0201: "Field defined twice in a structure",
+    S0202: "Static expression defined twice",
+    S0203: "Invalid import path",
+    S0204: "Unknown package",
+    S0205: "Invalid built-in import",
+
+    S0301: "Source file cannot be read",
+    S0302: "No file to run",
+    S0303: "Manifest cannot be read",
+    S0304: "Invalid manifest",
+    S0305: "Dependency cannot be located",
+    S0306: "Circular dependency",
+    S0307: "Dependency does not match the manifest",
+    S0308: "Unknown error code",
+
+    S0401: "Undefined variable",
+    S0402: "'@self' used outside of a method",
+    S0403: "Input variable out of range",
+    S0404: "No function matches the call",
+    S0405: "Unknown or inaccessible structure",
+    S0406: "Assignment to a static expression",
+    S0407: "Invalid field access",
+
+    S0501: "Assigned value of the wrong type",
+    S0502: "List element of the wrong type",
+    S0503: "Non-boolean 'if' condition",
+    S0504: "Structure built with invalid fields",
+    S0505: "Function returned a value of the wrong type",
+    S0506: "Built-in function called with invalid arguments",
+
+    S0601: "Missing or duplicate 'main' function",
+    S0602: "'main' does not return a number",
+    S0603: "Wrong number of program arguments",
+    S0604: "Invalid program argument",
+
+    S0701: "Index out of range",
+    S0702: "Argument out of the accepted range",
+    S0703: "String cannot be converted to a number",
+    S0704: "File operation failed",
+    S0705: "Standard input not available",
+
+    S0901: "Internal error of the interpreter",
+}
+
+
+impl std::fmt::Display for ErrCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+
+
+
+/// Return the code with the given name (case insensitive), or an error message with a suggestion
+pub fn find_code(name: &str) -> Result<ErrCode, String> {
+    match ErrCode::ALL.iter().find(|c| c.as_str().eq_ignore_ascii_case(name)) {
+        Some(c) => Ok(*c),
+        None => {
+            let suggestion = suggestions::did_you_mean(&name.to_uppercase(), ErrCode::ALL.iter().map(|c| c.as_str()));
+            Err(format!("Unknown error code '{}'{}", name, suggestion))
+        }
+    }
+}
+
+
+/// Return the text printed by `sloth --explain`
+pub fn explain(code: ErrCode) -> String {
+    format!("{}: {}\n\n{}", code, code.get_summary(), code.get_explanation().trim_end())
+}
@@ -0,0 +1,522 @@
+//! Debug adapter speaking the Debug Adapter Protocol over the standard input and output.
+//! The program is launched once the client is configured, and followed with an [ExecutionHook]: when it pauses,
+//! the requests of the client (stack trace, variables, evaluation...) are answered until one of them resumes
+//! the execution. The text printed by the program is sent to the client as output events.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, Stdout};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde_json::{json, Value as Json};
+
+use crate::builtins::io::{redirect_input, redirect_output};
+use crate::checker;
+use crate::debugger::evaluate_input;
+use crate::errors::{Error, Frame};
+use crate::lsp::{get_packages, read_message, write_message};
+use crate::parser;
+use crate::position::Position;
+use crate::repl::value_to_string;
+use crate::sloth::function::SlothFunction;
+use crate::sloth::hooks::{self, ExecutionHook, StepMode};
+use crate::sloth::program::{SlothProgram, DEFAULT_MAX_ERRORS};
+use crate::sloth::scope::Scope;
+use crate::sloth::value::Value;
+
+
+
+// the program runs in a single thread
+const THREAD_ID: u64 = 1;
+
+
+
+/// Messages exchanged with the client, numbered in the order they are sent.
+/// The standard input is only locked while a message is read
+struct Connection {
+    writer: Stdout,
+    seq: u64,
+}
+
+
+impl Connection {
+    fn new() -> Connection {
+        Connection {writer: io::stdout(), seq: 0}
+    }
+
+
+    /// Read the next message of the client. Return None at the end of the input
+    fn read(&mut self) -> io::Result<Option<Json>> {
+        read_message(&mut io::stdin().lock())
+    }
+
+
+    fn send(&mut self, mut message: Json) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&mut self.writer, &message)
+    }
+
+
+    /// Answer the request with the body of its response, or an error message
+    fn respond(&mut self, request: &Json, result: Result<Json, String>) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message)
+        }
+        self.send(response)
+    }
+
+
+    fn event(&mut self, event: &str, body: Json) -> io::Result<()> {
+        self.send(json!({"type": "event", "event": event, "body": body}))
+    }
+}
+
+
+
+
+/// Return the result of a communication with the client, exiting if the client is unreachable
+/// (the execution cannot continue without it)
+fn or_exit<T>(result: io::Result<T>) -> T {
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Unable to communicate with the client: {}", e);
+            std::process::exit(1)
+        }
+    }
+}
+
+
+/// Return the text of the error shown to the client
+fn error_text(error: &Error) -> String {
+    let code = error.code.map(|c| format!(" [{}]", c)).unwrap_or_default();
+    match error.position_trace.last() {
+        Some(p) => format!("{}{}: {} {}", error.message.get_title(), code, error.message, p),
+        None => format!("{}{}: {}", error.message.get_title(), code, error.message)
+    }
+}
+
+
+/// Report the error to the client (and on the standard error)
+fn report_error(connection: &Rc<RefCell<Connection>>, error: &Error) -> io::Result<()> {
+    error.print();
+    connection.borrow_mut().event("output", json!({"category": "stderr", "output": error_text(error) + "\n"}))
+}
+
+
+
+
+/// Program to debug, described by the launch request
+struct Launch {
+    program: String,
+    arguments: Vec<String>,
+    stop_on_entry: bool,
+}
+
+
+/// What happens after a request of the client
+enum Action {
+    Wait,
+    Launch(Launch),
+    Configured,
+    Resume(StepMode),
+    Disconnect,
+}
+
+
+/// Scope or value whose content is shown to the client while the execution is paused.
+/// It is identified by its index in the list of references + 1 (0 meaning no content)
+enum Reference {
+    Scope(Rc<RefCell<Scope>>),
+    Value(Rc<RefCell<Value>>),
+}
+
+
+
+
+pub struct Adapter {
+    connection: Rc<RefCell<Connection>>,
+    mode: StepMode,
+    entry: bool,                                    // whether the next pause is the one on entry
+    breakpoints: HashMap<PathBuf, Vec<usize>>,      // lines (starting at 1) by canonical path of the file
+    paths: HashMap<String, PathBuf>,                // canonical paths of the files of the program
+    stack: Vec<(Frame, Rc<RefCell<Scope>>)>,        // function calls being executed, the deepest last
+    references: Vec<Reference>,                     // cleared when the execution resumes
+    inputs_count: usize,                            // used to name the evaluated inputs in the positions of the errors
+}
+
+
+
+
+impl Adapter {
+    fn new(connection: Rc<RefCell<Connection>>) -> Adapter {
+        Adapter {
+            connection,
+            mode: StepMode::Continue,
+            entry: false,
+            breakpoints: HashMap::new(),
+            paths: HashMap::new(),
+            stack: Vec::new(),
+            references: Vec::new(),
+            inputs_count: 0,
+        }
+    }
+
+
+    /// Return the canonical path of the file, to compare it with the ones of the client
+    fn canonical_path(&mut self, filename: &str) -> PathBuf {
+        self.paths.entry(filename.to_string())
+            .or_insert_with(|| Path::new(filename).canonicalize().unwrap_or_else(|_| PathBuf::from(filename)))
+            .clone()
+    }
+
+
+    fn is_on_breakpoint(&mut self, position: &Position) -> bool {
+        if self.breakpoints.is_empty() {return false}
+
+        let path = self.canonical_path(&position.filename);
+        self.breakpoints.get(&path).is_some_and(|lines| lines.contains(&(position.line + 1)))
+    }
+
+
+    /// Replace the breakpoints of a file
+    fn set_breakpoints(&mut self, arguments: &Json) -> Result<Json, String> {
+        let path = arguments["source"]["path"].as_str().ok_or("Missing source path")?;
+        let path = Path::new(path).canonicalize().unwrap_or_else(|_| PathBuf::from(path));
+
+        let lines: Vec<usize> = arguments["breakpoints"].as_array()
+            .map(|b| b.iter().filter_map(|b| b["line"].as_u64()).map(|l| l as usize).collect())
+            .unwrap_or_default();
+
+        let breakpoints: Vec<Json> = lines.iter().map(|l| json!({"verified": true, "line": l})).collect();
+        self.breakpoints.insert(path, lines);
+        Ok(json!({"breakpoints": breakpoints}))
+    }
+
+
+    fn push_reference(&mut self, reference: Reference) -> usize {
+        self.references.push(reference);
+        self.references.len()
+    }
+
+
+    /// Return the description of a variable. Non-empty lists and objects can be expanded by the client
+    fn variable(&mut self, name: &str, value: &Rc<RefCell<Value>>) -> Json {
+        let (text, type_name, expandable) = {
+            let v = value.borrow();
+            let expandable = match &*v {
+                Value::List(_, l) => !l.is_empty(),
+                Value::Object(_) => true,
+                _ => false
+            };
+            (value_to_string(&v), v.get_type().to_string(), expandable)
+        };
+
+        let reference = if expandable {self.push_reference(Reference::Value(value.clone()))} else {0};
+        json!({"name": name, "value": text, "type": type_name, "variablesReference": reference})
+    }
+
+
+    /// Return the variables of a scope, the elements of a list or the fields of an object
+    fn variables(&mut self, arguments: &Json) -> Result<Json, String> {
+        let index = arguments["variablesReference"].as_u64().unwrap_or(0) as usize;
+
+        let children: Vec<(String, Rc<RefCell<Value>>)> = match self.references.get(index.wrapping_sub(1)) {
+            Some(Reference::Scope(scope)) => {
+                let mut variables = scope.borrow().get_variables();
+                variables.sort_by(|a, b| a.0.cmp(&b.0));
+                variables
+            },
+            Some(Reference::Value(value)) => match &*value.borrow() {
+                Value::List(_, elements) => elements.iter().enumerate().map(|(i, e)| (format!("[{}]", i), e.clone())).collect(),
+                Value::Object(object) => {
+                    let (names, values) = object.get_fields();
+                    names.into_iter().zip(values).collect()
+                },
+                _ => Vec::new()
+            },
+            None => return Err(format!("Unknown variables reference {}", index))
+        };
+
+        let variables: Vec<Json> = children.iter().map(|(n, v)| self.variable(n, v)).collect();
+        Ok(json!({"variables": variables}))
+    }
+
+
+    /// Return the scope of the frame given by the client, the deepest call being the frame 0
+    fn frame_scope(&self, arguments: &Json) -> Result<Rc<RefCell<Scope>>, String> {
+        let id = arguments["frameId"].as_u64().unwrap_or(0) as usize;
+        match self.stack.len().checked_sub(id + 1) {
+            Some(i) => Ok(self.stack[i].1.clone()),
+            None => Err(format!("Unknown frame {}", id))
+        }
+    }
+
+
+    /// Return the function calls, the deepest first, with the position they are executing
+    fn stack_trace(&mut self, position: &Position) -> Json {
+        let frames: Vec<Frame> = self.stack.iter().rev().map(|(f, _)| f.clone()).collect();
+        let mut current = Some(position.clone());
+        let mut stack_frames = Vec::new();
+
+        for (i, frame) in frames.iter().enumerate() {
+            let mut stack_frame = json!({"id": i, "name": frame.to_string(), "line": 0, "column": 0});
+
+            if let Some(p) = &current {
+                let path = self.canonical_path(&p.filename);
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                stack_frame["source"] = json!({"name": name, "path": path});
+                stack_frame["line"] = json!(p.line + 1);
+                stack_frame["column"] = json!(p.first_column + 1);
+            }
+
+            stack_frames.push(stack_frame);
+            current = frame.call_position.clone();
+        }
+
+        json!({"stackFrames": stack_frames, "totalFrames": frames.len()})
+    }
+
+
+    /// Evaluate the expression of the client in the scope of the given frame
+    fn evaluate(&mut self, arguments: &Json, program: &mut SlothProgram) -> Result<Json, String> {
+        let expression = arguments["expression"].as_str().unwrap_or_default();
+        let scope = self.frame_scope(arguments)?;
+
+        self.inputs_count += 1;
+        let name = format!("<debug:{}>", self.inputs_count);
+
+        match evaluate_input(&name, expression, &scope, program) {
+            Ok(Some(value)) => {
+                let variable = self.variable(expression, &value);
+                Ok(json!({"result": variable["value"], "type": variable["type"], "variablesReference": variable["variablesReference"]}))
+            },
+            Ok(None) => Ok(json!({"result": "", "variablesReference": 0})),
+            Err(errors) => Err(errors.iter().map(error_text).collect::<Vec<String>>().join("\n"))
+        }
+    }
+
+
+    /// Answer a request of the client. The state of the program is given if it is paused
+    fn handle_request(&mut self, request: &Json, paused: Option<(&Position, &mut SlothProgram)>) -> io::Result<Action> {
+        let arguments = &request["arguments"];
+        let mut action = Action::Wait;
+
+        let result = match (request["command"].as_str().unwrap_or_default(), paused) {
+            ("initialize", _) => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsEvaluateForHovers": true,
+            })),
+
+            ("launch", _) => match (arguments["program"].as_str(), arguments["cwd"].as_str()) {
+                (None, _) => Err("Missing 'program' in the launch arguments".to_string()),
+                (Some(_), Some(cwd)) if std::env::set_current_dir(cwd).is_err() => {
+                    Err(format!("Unable to use '{}' as working directory", cwd))
+                },
+                (Some(program), _) => {
+                    let arguments_list = arguments["args"].as_array()
+                        .map(|a| a.iter().filter_map(|a| a.as_str()).map(|a| a.to_string()).collect())
+                        .unwrap_or_default();
+
+                    action = Action::Launch(Launch {
+                        program: program.to_string(),
+                        arguments: arguments_list,
+                        stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+                    });
+                    Ok(json!({}))
+                }
+            },
+
+            ("setBreakpoints", _) => self.set_breakpoints(arguments),
+            ("configurationDone", _) => {
+                action = Action::Configured;
+                Ok(json!({}))
+            },
+            ("threads", _) => Ok(json!({"threads": [{"id": THREAD_ID, "name": "main"}]})),
+            ("disconnect" | "terminate", _) => {
+                action = Action::Disconnect;
+                Ok(json!({}))
+            },
+
+            // only while the program is paused
+            ("stackTrace", Some((position, _))) => Ok(self.stack_trace(position)),
+            ("scopes", Some(_)) => self.frame_scope(arguments).map(|scope| {
+                let reference = self.push_reference(Reference::Scope(scope));
+                json!({"scopes": [{"name": "Locals", "variablesReference": reference, "expensive": false}]})
+            }),
+            ("variables", Some(_)) => self.variables(arguments),
+            ("evaluate", Some((_, program))) => self.evaluate(arguments, program),
+
+            ("continue", Some(_)) => {
+                action = Action::Resume(StepMode::Continue);
+                Ok(json!({"allThreadsContinued": true}))
+            },
+            ("next", Some(_)) => {
+                action = Action::Resume(StepMode::Next(self.stack.len()));
+                Ok(json!({}))
+            },
+            ("stepIn", Some(_)) => {
+                action = Action::Resume(StepMode::Step);
+                Ok(json!({}))
+            },
+            ("stepOut", Some(_)) => {
+                action = Action::Resume(StepMode::Out(self.stack.len()));
+                Ok(json!({}))
+            },
+
+            ("stackTrace" | "scopes" | "variables" | "evaluate" | "continue" | "next" | "stepIn" | "stepOut", None) => {
+                Err("The program is not paused".to_string())
+            },
+            (command, _) => Err(format!("Unsupported request '{}'", command))
+        };
+
+        let mut connection = self.connection.borrow_mut();
+        connection.respond(request, result)?;
+        if request["command"] == "initialize" {connection.event("initialized", json!({}))?}
+
+        Ok(action)
+    }
+
+
+    /// Notify the client that the execution is paused, and answer its requests until one resumes it
+    fn pause(&mut self, reason: &str, position: &Position, program: &mut SlothProgram) -> io::Result<()> {
+        self.connection.borrow_mut().event("stopped", json!({"reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true}))?;
+
+        loop {
+            let message = self.connection.borrow_mut().read()?;
+            let request = match message {
+                Some(r) => r,
+                None => std::process::exit(1)
+            };
+
+            match self.handle_request(&request, Some((position, &mut *program)))? {
+                Action::Resume(mode) => {
+                    self.mode = mode;
+                    self.references.clear();
+                    return Ok(())
+                },
+                Action::Disconnect => std::process::exit(0),
+                _ => ()
+            }
+        }
+    }
+}
+
+
+
+
+impl ExecutionHook for Adapter {
+    fn before_statement(&mut self, position: &Position, _scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) {
+        let breakpoint = self.is_on_breakpoint(position);
+        if !breakpoint && !self.mode.pauses_at(self.stack.len()) {return}
+
+        let reason = if breakpoint {"breakpoint"} else if self.entry {"entry"} else {"step"};
+        self.entry = false;
+        or_exit(self.pause(reason, position, program));
+    }
+
+
+    fn enter_function(&mut self, _function: &dyn SlothFunction, frame: &Frame, scope: &Rc<RefCell<Scope>>) {
+        self.stack.push((frame.clone(), scope.clone()));
+    }
+
+
+    fn exit_function(&mut self, _function: &dyn SlothFunction, _frame: &Frame, _result: &Result<Rc<RefCell<Value>>, Error>) {
+        self.stack.pop();
+    }
+}
+
+
+
+
+/// Build and check the program to debug
+fn build_program(filename: &str) -> Result<SlothProgram, Vec<Error>> {
+    let program = parser::build_program(filename.to_string(), true, true, Vec::new(), get_packages(filename), DEFAULT_MAX_ERRORS).map_err(|(e, _)| e)?;
+
+    let errors = checker::check_program(&program);
+    if !errors.is_empty() {return Err(errors)}
+    Ok(program)
+}
+
+
+
+
+/// Run the debug adapter until the client disconnects. Return the exit code of the debugged program
+pub fn run() -> io::Result<i32> {
+    let connection = Rc::new(RefCell::new(Connection::new()));
+    let mut adapter = Adapter::new(connection.clone());
+
+    // the client sets the breakpoints before the program is launched
+    let mut launch = None;
+    let mut configured = false;
+    let launch = loop {
+        let message = connection.borrow_mut().read()?;
+        let request = match message {
+            Some(r) => r,
+            None => return Ok(1)
+        };
+
+        match adapter.handle_request(&request, None)? {
+            Action::Launch(l) => launch = Some(l),
+            Action::Configured => configured = true,
+            Action::Disconnect => return Ok(0),
+            _ => ()
+        }
+
+        if configured {
+            if let Some(l) = launch.take() {break l}
+        }
+    };
+
+    let code = match build_program(&launch.program) {
+        Ok(mut program) => {
+            let output = connection.clone();
+            redirect_output(Box::new(move |text| {
+                or_exit(output.borrow_mut().event("output", json!({"category": "stdout", "output": text})))
+            }));
+            // the standard input carries the messages of the client
+            redirect_input(Box::new(|| Err("The standard input is used by the debug adapter, it cannot be read by the program".to_string())));
+
+            adapter.mode = if launch.stop_on_entry {StepMode::Step} else {StepMode::Continue};
+            adapter.entry = launch.stop_on_entry;
+            hooks::add_hook(Box::new(adapter));
+
+            match unsafe {program.run(launch.arguments)} {
+                Ok(Value::Number(x)) => x as i32,
+                Ok(_) => panic!("The main function must return a Number value"),
+                Err(e) => {
+                    report_error(&connection, &e)?;
+                    1
+                }
+            }
+        },
+        Err(errors) => {
+            for e in &errors {report_error(&connection, e)?}
+            1
+        }
+    };
+
+    let mut connection = connection.borrow_mut();
+    connection.event("exited", json!({"exitCode": code}))?;
+    connection.event("terminated", json!({}))?;
+
+    // the program has terminated, the client can only disconnect
+    while let Some(request) = connection.read()? {
+        if request["command"] == "disconnect" {
+            connection.respond(&request, Ok(json!({})))?;
+            break
+        }
+        connection.respond(&request, Err("The program has terminated".to_string()))?;
+    }
+
+    Ok(code)
+}
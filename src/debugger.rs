@@ -0,0 +1,267 @@
+//! Terminal debugger. The program pauses before its statements: the variables of the scope can be printed,
+//! expressions evaluated in it, breakpoints set, and the execution resumed step by step.
+//! It follows the execution with an [ExecutionHook].
+
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use crate::errors::{Error, Frame};
+use crate::parser;
+use crate::position::Position;
+use crate::repl::value_to_string;
+use crate::sloth::function::SlothFunction;
+use crate::sloth::hooks::{ExecutionHook, StepMode};
+use crate::sloth::program::SlothProgram;
+use crate::sloth::scope::Scope;
+use crate::sloth::statement::Statement;
+use crate::sloth::value::Value;
+use crate::sources;
+
+
+
+const PROMPT: &str = "(debug) ";
+
+// number of lines printed before and after the current one by 'list'
+const LIST_CONTEXT: usize = 3;
+
+const HELP: &str = "\
+s, step              run until the next statement, entering function calls
+n, next              run until the next statement of this function, stepping over function calls
+o, out               run until the current function returns
+c, continue          run until a breakpoint
+b, break [FILE:]LINE set a breakpoint (in the current file if FILE is not given)
+d, delete [FILE:]LINE remove a breakpoint
+breakpoints          list the breakpoints
+v, vars              print the variables of the current scope
+p, print EXPR        evaluate an expression (or run a statement) in the current scope
+bt, stack            print the call stack
+l, list              print the source around the current statement
+q, quit              stop the program
+An empty line repeats the last command";
+
+
+
+pub struct Debugger {
+    editor: Editor<()>,
+    mode: StepMode,
+    breakpoints: Vec<(String, usize)>,              // file and line (starting at 1)
+    stack: Vec<(Frame, Rc<RefCell<Scope>>)>,        // function calls being executed, the deepest last
+    last_command: String,
+    inputs_count: usize,                            // used to name the evaluated inputs in the positions of the errors
+}
+
+
+
+
+/// Return whether the breakpoint (file and line starting at 1) is on the given position
+fn is_on_breakpoint(breakpoint: &(String, usize), position: &Position) -> bool {
+    let (file, line) = breakpoint;
+    *line == position.line + 1 && (*file == position.filename || Path::new(&position.filename).ends_with(file))
+}
+
+
+
+
+/// Parse and run the input in the scope of a paused program. Return the value of its last statement
+/// if it is an expression. Definitions are refused, as the program is running
+pub fn evaluate_input(name: &str, input: &str, scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<Option<Rc<RefCell<Value>>>, Vec<Error>> {
+    let statements = parser::parse_chunk(name, input, program, false, false)?;
+    let mut value = None;
+
+    for (i, statement) in statements.iter().enumerate() {
+        value = match statement {
+            Statement::ExpressionCall(expr, _) if i + 1 == statements.len() => unsafe {
+                Some(expr.evaluate(scope.clone(), program, false).map_err(|e| vec![e])?)
+            },
+            s => unsafe {
+                s.apply(scope.clone(), program).map_err(|e| vec![e])?;
+                None
+            }
+        };
+    }
+
+    Ok(value)
+}
+
+
+
+
+impl Debugger {
+    pub fn new() -> Result<Debugger, ReadlineError> {
+        Ok(Debugger {
+            editor: Editor::<()>::new()?,
+            mode: StepMode::Step,
+            breakpoints: Vec::new(),
+            stack: Vec::new(),
+            last_command: String::new(),
+            inputs_count: 0,
+        })
+    }
+
+
+    /// Return whether the execution pauses before a statement at the given position
+    fn should_pause(&self, position: &Position) -> bool {
+        self.mode.pauses_at(self.stack.len()) || self.breakpoints.iter().any(|b| is_on_breakpoint(b, position))
+    }
+
+
+    /// Return the breakpoint described by '[FILE:]LINE', the file being the current one if not given
+    fn parse_breakpoint(spec: &str, position: &Position) -> Result<(String, usize), String> {
+        let (file, line) = match spec.rsplit_once(':') {
+            Some((f, l)) => (f.to_string(), l),
+            None => (position.filename.clone(), spec)
+        };
+
+        match line.trim().parse::<usize>() {
+            Ok(l) if l > 0 => Ok((file, l)),
+            _ => Err(format!("Invalid breakpoint '{}'. Expected [FILE:]LINE, like 'main.slo:12'", spec))
+        }
+    }
+
+
+    /// Print the lines of the source around the position, marking the current one
+    fn print_source(position: &Position, context: usize) {
+        let source = match sources::get(&position.filename) {
+            Some(s) => s,
+            None => return
+        };
+
+        let first = position.line.saturating_sub(context);
+        for (i, line) in source.lines().enumerate().skip(first).take(2 * context + 1) {
+            let marker = if i == position.line {"->"} else {"  "};
+            println!("{} {:>4} | {}", marker, i + 1, line);
+        }
+    }
+
+
+    fn print_variables(scope: &Rc<RefCell<Scope>>) {
+        let scope = scope.borrow();
+        let mut variables = scope.get_variables();
+        variables.sort_by(|a, b| a.0.cmp(&b.0));
+
+        if variables.is_empty() {println!("No variables")}
+        for (name, value) in variables {
+            let value = value.borrow();
+            println!("{} = {}: {}", name, value_to_string(&value), value.get_type());
+        }
+    }
+
+
+    /// Print the function calls, the deepest first, with the position they are executing
+    fn print_stack(&self, position: &Position) {
+        let mut current = Some(position.clone());
+
+        for (i, (frame, _)) in self.stack.iter().rev().enumerate() {
+            match &current {
+                Some(p) => println!("#{} {} at {}", i, frame, p),
+                None => println!("#{} {}", i, frame)
+            }
+            current = frame.call_position.clone();
+        }
+    }
+
+
+    /// Parse and run the input in the paused scope, printing the value of its last statement if it is an expression
+    fn evaluate(&mut self, input: &str, scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) {
+        self.inputs_count += 1;
+        let name = format!("<debug:{}>", self.inputs_count);
+
+        match evaluate_input(&name, input, scope, program) {
+            Ok(Some(v)) => {
+                let v = v.borrow();
+                println!("{}: {}", value_to_string(&v), v.get_type())
+            },
+            Ok(None) => (),
+            Err(errors) => for e in &errors {e.print()}
+        }
+    }
+
+
+    /// Read and run the commands until one resumes the execution
+    fn prompt(&mut self, position: &Position, scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) {
+        loop {
+            let input = match self.editor.readline(PROMPT) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(_) => std::process::exit(0)
+            };
+
+            // an empty line repeats the last command
+            let input = match input.trim() {
+                "" => self.last_command.clone(),
+                i => {
+                    self.editor.add_history_entry(i);
+                    self.last_command = i.to_string();
+                    i.to_string()
+                }
+            };
+            let (command, argument) = match input.split_once(' ') {
+                Some((c, a)) => (c, a.trim()),
+                None => (input.as_str(), "")
+            };
+
+            match command {
+                "" => (),
+                "s" | "step" => {self.mode = StepMode::Step; return},
+                "n" | "next" => {self.mode = StepMode::Next(self.stack.len()); return},
+                "o" | "out" => {self.mode = StepMode::Out(self.stack.len()); return},
+                "c" | "continue" => {self.mode = StepMode::Continue; return},
+
+                "b" | "break" => match Self::parse_breakpoint(argument, position) {
+                    Ok(b) => {
+                        println!("Breakpoint set at {}:{}", b.0, b.1);
+                        if !self.breakpoints.contains(&b) {self.breakpoints.push(b)}
+                    },
+                    Err(e) => println!("{}", e)
+                },
+                "d" | "delete" => match Self::parse_breakpoint(argument, position) {
+                    Ok(b) => match self.breakpoints.iter().position(|o| *o == b) {
+                        Some(i) => {self.breakpoints.remove(i);},
+                        None => println!("No breakpoint at {}:{}", b.0, b.1)
+                    },
+                    Err(e) => println!("{}", e)
+                },
+                "breakpoints" => {
+                    if self.breakpoints.is_empty() {println!("No breakpoints")}
+                    for (file, line) in &self.breakpoints {println!("{}:{}", file, line)}
+                },
+
+                "v" | "vars" => Self::print_variables(scope),
+                "p" | "print" => self.evaluate(argument, scope, program),
+                "bt" | "stack" => self.print_stack(position),
+                "l" | "list" => Self::print_source(position, LIST_CONTEXT),
+                "h" | "help" => println!("{}", HELP),
+                "q" | "quit" => std::process::exit(0),
+
+                c => println!("Unknown command '{}'. Type 'help' for the list of commands", c)
+            }
+        }
+    }
+}
+
+
+
+
+impl ExecutionHook for Debugger {
+    fn before_statement(&mut self, position: &Position, scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) {
+        if !self.should_pause(position) {return}
+
+        println!("{}", position);
+        Self::print_source(position, 0);
+        self.prompt(position, scope, program);
+    }
+
+
+    fn enter_function(&mut self, _function: &dyn SlothFunction, frame: &Frame, scope: &Rc<RefCell<Scope>>) {
+        self.stack.push((frame.clone(), scope.clone()));
+    }
+
+
+    fn exit_function(&mut self, _function: &dyn SlothFunction, _frame: &Frame, _result: &Result<Rc<RefCell<Value>>, Error>) {
+        self.stack.pop();
+    }
+}
@@ -1,6 +1,94 @@
 //! Errors and warnings returned by the interpreter.
 
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+use serde_json::{json, Value as Json};
+
+use crate::codes::ErrCode;
 use crate::position::Position;
+use crate::sources;
+use crate::suggestions;
+use crate::sloth::types::Type;
+
+
+
+/// Format in which the errors and warnings are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// Colored excerpts of the source, for humans
+    Human,
+    /// One JSON object per diagnostic, written to stderr
+    Json,
+}
+
+static JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+
+/// Set the format of every error and warning printed from now on
+pub fn set_error_format(format: ErrorFormat) {
+    JSON_FORMAT.store(format == ErrorFormat::Json, Ordering::Relaxed);
+}
+
+
+fn is_json_format() -> bool {
+    JSON_FORMAT.load(Ordering::Relaxed)
+}
+
+
+/// When the errors and warnings are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Only when the standard error is a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+static COLORED: AtomicBool = AtomicBool::new(false);
+
+
+/// Set whether the errors and warnings printed from now on are colored
+pub fn set_color_choice(choice: ColorChoice) {
+    let colored = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        // see https://no-color.org: any non-empty value disables the colors
+        ColorChoice::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+    };
+    COLORED.store(colored, Ordering::Relaxed);
+}
+
+
+// ANSI escape codes used by the diagnostics
+pub const RED: &str = "\x1b[31m";
+pub const LIGHT_RED: &str = "\x1b[91m";
+pub const YELLOW: &str = "\x1b[33m";
+pub const LIGHT_YELLOW: &str = "\x1b[93m";
+pub const GREY: &str = "\x1b[90m";
+pub const BLUE: &str = "\x1b[94m";
+pub const RESET: &str = "\x1b[0m";
+
+
+/// Return the given escape code, or an empty string if the diagnostics are not colored
+pub fn color(code: &'static str) -> &'static str {
+    match COLORED.load(Ordering::Relaxed) {
+        true => code,
+        false => ""
+    }
+}
+
+
+/// Return the JSON representation of a position, with lines and columns starting at 1
+fn position_to_json(p: &Position) -> Json {
+    json!({
+        "file": p.filename,
+        "start": {"line": p.line + 1, "column": p.first_column + 1},
+        "end": {"line": p.last_line + 1, "column": p.last_column.map(|c| c + 1)},
+    })
+}
+
 
 /// Represents an error message returned by the interpreter, at build time or at run time.  
 /// This include its type and its message, as a string.
@@ -19,9 +107,33 @@ pub enum ErrMsg {
     ImportError(String),
     DefinitionError(String),
     FunctionError(String),
+    ManifestError(String),
+    NameError(String),
 }
 
 impl ErrMsg {
+    /// Return the name of the kind of error, as used in the JSON diagnostics
+    pub fn get_kind(&self) -> &'static str {
+        match self {
+            ErrMsg::SyntaxError(..) => "SyntaxError",
+            ErrMsg::NoEntryPoint(..) => "NoEntryPoint",
+            ErrMsg::FileError(..) => "FileError",
+            ErrMsg::InvalidArguments(..) => "InvalidArguments",
+            ErrMsg::UnexpectedExpression(..) => "UnexpectedExpression",
+            ErrMsg::RuntimeError(..) => "RuntimeError",
+            ErrMsg::UnexpectedEOF(..) => "UnexpectedEOF",
+            ErrMsg::TypeError(..) => "TypeError",
+            ErrMsg::RustError(..) => "RustError",
+            ErrMsg::ReturnValueError(..) => "ReturnValueError",
+            ErrMsg::ImportError(..) => "ImportError",
+            ErrMsg::DefinitionError(..) => "DefinitionError",
+            ErrMsg::FunctionError(..) => "FunctionError",
+            ErrMsg::ManifestError(..) => "ManifestError",
+            ErrMsg::NameError(..) => "NameError",
+        }
+    }
+
+
     pub fn get_title(&self) -> String {
         match self {
             ErrMsg::SyntaxError(..) => "SYNTAX ERROR",
@@ -37,6 +149,8 @@ impl ErrMsg {
             ErrMsg::ImportError(..) => "IMPORT ERROR",
             ErrMsg::DefinitionError(..) => "DEFINITION ERROR",
             ErrMsg::FunctionError(..) => "FUNCTION ERROR",
+            ErrMsg::ManifestError(..) => "MANIFEST ERROR",
+            ErrMsg::NameError(..) => "NAME ERROR",
         }.to_string()
     }
 }
@@ -57,18 +171,52 @@ impl std::fmt::Display for ErrMsg {
             ErrMsg::ImportError(e) => write!(f, "{}", e),
             ErrMsg::DefinitionError(e) => write!(f, "{}", e),
             ErrMsg::FunctionError(e) => write!(f, "{}", e),
+            ErrMsg::ManifestError(e) => write!(f, "{}", e),
+            ErrMsg::NameError(e) => write!(f, "{}", e),
         }
     }
 }
 
 
+/// A function call of the call stack of an error: the function called and where it was called from
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub name: String,
+    pub module: Option<String>,
+    pub owner_type: Option<Type>,
+    pub call_position: Option<Position>,    // None for the call of the main function
+}
+
+impl Frame {
+    /// Return whether both frames are calls of the same function (maybe from different positions)
+    fn same_function(&self, other: &Frame) -> bool {
+        self.name == other.name && self.module == other.module && self.owner_type == other.owner_type
+    }
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(m) = &self.module {write!(f, "{}:", m)?}
+        write!(f, "{}", self.name)?;
+        if let Some(t) = &self.owner_type {write!(f, " for {}", t)?}
+        Ok(())
+    }
+}
+
+
+
+
 /// Represents an error returned by the interpreter, at build time or at run time.  
 /// It is a combinaison of a message ([ErrMsg]) and a Vec of [Position],
 /// representing the backtrace of the error (= with tokens in a source file).
+/// Runtime errors also store the function calls they went through, the deepest first.
+/// Its code identifies the kind of diagnostic (see [ErrCode])
 #[derive(Debug, Clone)]
 pub struct Error {
     pub message: ErrMsg,
-    pub position_trace: Vec<Position>
+    pub code: Option<ErrCode>,
+    pub position_trace: Vec<Position>,
+    pub call_stack: Vec<Frame>,
 }
 
 
@@ -84,60 +232,124 @@ impl Error {
             }
         };
 
-        Error {message: message, position_trace: pos}
+        Error {message: message, code: None, position_trace: pos, call_stack: Vec::new()}
+    }
+
+
+    /// Return a copy of the error with the given code
+    pub fn with_code(mut self, code: ErrCode) -> Error {
+        self.code = Some(code);
+        self
     }
 
 
     /// print the trace from the deeper to the shallower, then exit the program
     pub fn abort(&self) {
-        // find the highest line error n° to determine the space required at the left of the backtrace line to fit line numbers
-        let max_n = self.position_trace.iter().map(|p| p.line + 1).max();
+        self.print();
+        std::process::exit(1)
+    }
 
-        let mut max_n = match max_n {
-            None => 0,
-            Some(n) => n.to_string().len() + 2
-        };
-        if max_n < 5 {max_n = 5}
+
+    /// Return the error as a JSON diagnostic. Its span is the deepest position of the trace
+    pub fn to_json(&self) -> Json {
+        let frames: Vec<Json> = self.call_stack.iter().map(|f| json!({
+            "function": f.name,
+            "module": f.module,
+            "owner_type": f.owner_type.as_ref().map(|t| t.to_string()),
+            "call_site": f.call_position.as_ref().map(position_to_json),
+        })).collect();
+
+        json!({
+            "severity": "error",
+            "kind": self.message.get_kind(),
+            "code": self.code.map(|c| c.as_str()),
+            "message": self.message.to_string(),
+            "span": self.position_trace.last().map(position_to_json),
+            "trace": self.position_trace.iter().map(position_to_json).collect::<Vec<Json>>(),
+            "call_stack": frames,
+        })
+    }
 
 
-        let mut raw_space = String::new();
-        for _ in 0..max_n {raw_space.push(' ')}
+    /// print the trace from the deeper to the shallower
+    pub fn print(&self) {
+        if is_json_format() {
+            eprintln!("{}", self.to_json());
+            return
+        }
+
+        let (red, light_red, reset) = (color(RED), color(LIGHT_RED), color(RESET));
+        let code = match self.code {
+            Some(c) => format!(" [{}]", c),
+            None => String::new()
+        };
+
+        // find the highest line error n° to determine the space required at the left of the backtrace line to fit line numbers
+        let max_n = self.position_trace.iter().map(|p| p.last_line + 1).max();
+        let raw_space = gutter_space(max_n);
 
         for (i, p) in self.position_trace.iter().enumerate() {
-            let filepath = std::path::Path::new(&p.filename);
-            let file_string = std::fs::read_to_string(filepath).expect(format!("Unable to read file {:?}", filepath.as_os_str()).as_str());
-            let lines: Vec<&str> = file_string.split('\n').collect();
-
-            if i == 0 {println!("\x1b[31m{raw_space}■ \x1b[91m{} (backtrace)\x1b[0m", self.message.get_title());}
-            else {println!("\x1b[31m{raw_space}v\x1b[0m");}
-            println!("\x1b[31m{raw_space}|\x1b[0m");
-            
-            let nb_space_before_line_n = max_n - ((p.line + 1).to_string().len() + 1);
-            let mut spaces = String::new();
-            for _ in 0..nb_space_before_line_n {spaces.push(' ')}
-            println!("\x1b[31m{}{} |\x1b[0m {}", spaces, p.line + 1, lines[p.line]);
-
-            print!("\x1b[31m{raw_space}| \x1b[91m");
-            for _ in 0..p.first_column {print!(" ")}
-
-            match p.last_column {
-                Some(n) => for _ in 0..(n - p.first_column + 1) {print!("^")},
-                None => for _ in 0..(lines[p.line].len() - p.first_column + 2) {print!("^")}
-            }
+            if i == 0 {eprintln!("{red}{raw_space}■ {light_red}{}{code} (backtrace){reset}", self.message.get_title());}
+            else {eprintln!("{red}{raw_space}v{reset}");}
+            eprintln!("{red}{raw_space}|{reset}");
 
-            println!("\x1b[90m         ({}:{})\x1b[0m", p.filename, p.line + 1);
+            print_excerpt(p, &raw_space, red, light_red);
         }
 
         if !self.position_trace.is_empty() {
-            println!("\x1b[31m{raw_space}|\x1b[0m");
-            print!("\x1b[31m{raw_space}=>");
+            eprintln!("{red}{raw_space}|{reset}");
+            eprint!("{red}{raw_space}=>");
         }
 
-        // Print the error
-        println!("\x1b[91m {}\x1b[0m", self.message);
-        
+        // Print the error. Without position, the code is printed with the message
+        match self.position_trace.is_empty() {
+            true => eprintln!("{light_red}{} {}{reset}", code.trim_start(), self.message),
+            false => eprintln!("{light_red} {}{reset}", self.message),
+        }
 
-        std::process::exit(1)
+        self.print_call_stack(&raw_space);
+
+        if let Some(c) = self.code {
+            eprintln!("{red}{raw_space}= {reset}Run 'sloth --explain {}' for more information", c);
+        }
+    }
+
+
+    /// Print the function calls the error went through, the most recent call last.
+    /// The consecutive calls of the same function (recursion) are collapsed
+    fn print_call_stack(&self, raw_space: &str) {
+        if self.call_stack.is_empty() {return}
+
+        let (red, grey, reset) = (color(RED), color(GREY), color(RESET));
+
+        eprintln!("{red}{raw_space}|{reset}");
+        eprintln!("{red}{raw_space}= {reset}Call stack (most recent call last):");
+
+        let frames: Vec<&Frame> = self.call_stack.iter().rev().collect();
+        let mut i = 0;
+        while i < frames.len() {
+            let frame = frames[i];
+            let location = match &frame.call_position {
+                Some(p) => format!("{grey}         (called at {}:{})", p.filename, p.line + 1),
+                None => String::new()
+            };
+            eprintln!("{raw_space}    {}{}{reset}", frame, location);
+
+            // number of following calls of the same function
+            let repeated = frames[i + 1..].iter().take_while(|f| f.same_function(frame)).count();
+            if repeated > 1 {
+                eprintln!("{raw_space}    {grey}... {} more frames of {}{reset}", repeated, frame);
+                i += repeated;
+            }
+            i += 1;
+        }
+    }
+
+
+    /// Return a copy of the error with the given function call added to its call stack
+    pub fn in_frame(mut self, frame: Frame) -> Error {
+        self.call_stack.push(frame);
+        self
     }
 
 
@@ -161,61 +373,182 @@ impl Error {
 
 
 
+/// Category of a warning. Each category can be disabled with '-W no-<name>' on the command line,
+/// or for a single definition with the '@allow(<name>)' attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    Semicolon,          // missing semicolon at the end of a statement
+    Redefinition,       // function or structure defined twice
+    Uppercase,          // name of a static expression not in uppercase
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 3] = [WarningKind::Semicolon, WarningKind::Redefinition, WarningKind::Uppercase];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            WarningKind::Semicolon => "semicolon",
+            WarningKind::Redefinition => "redefinition",
+            WarningKind::Uppercase => "uppercase",
+        }
+    }
+
+    /// Return the category with the given name
+    pub fn from_name(name: &str) -> Result<WarningKind, String> {
+        match WarningKind::ALL.iter().find(|k| k.get_name() == name) {
+            Some(k) => Ok(*k),
+            None => {
+                let suggestion = suggestions::did_you_mean(name, WarningKind::ALL.iter().map(|k| k.get_name()));
+                Err(format!("Unknown warning category '{}'{}", name, suggestion))
+            }
+        }
+    }
+
+    fn bit(&self) -> u32 {
+        1 << (*self as u32)
+    }
+}
+
+
+static DISABLED_WARNINGS: AtomicU32 = AtomicU32::new(0);     // categories disabled on the command line
+static ALLOWED_WARNINGS: AtomicU32 = AtomicU32::new(0);      // categories allowed in the definition being parsed
+static DENY_WARNINGS: AtomicBool = AtomicBool::new(false);
+static WARNINGS_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// warnings kept instead of printed, for the language server
+static CAPTURED_WARNINGS: Mutex<Option<Vec<Warning>>> = Mutex::new(None);
+
+
+/// Parse a '-W' flag: the name of a category to enable, or 'no-' followed by the name of a category to disable
+pub fn parse_warning_flag(flag: &str) -> Result<(WarningKind, bool), String> {
+    match flag.strip_prefix("no-") {
+        Some(name) => Ok((WarningKind::from_name(name)?, false)),
+        None => Ok((WarningKind::from_name(flag)?, true)),
+    }
+}
+
+
+/// Enable or disable the warnings of the given category
+pub fn set_warning_enabled(kind: WarningKind, enabled: bool) {
+    match enabled {
+        true => DISABLED_WARNINGS.fetch_and(!kind.bit(), Ordering::Relaxed),
+        false => DISABLED_WARNINGS.fetch_or(kind.bit(), Ordering::Relaxed),
+    };
+}
+
+
+/// Set whether the warnings are reported as errors, making the build fail
+pub fn set_deny_warnings(deny: bool) {
+    DENY_WARNINGS.store(deny, Ordering::Relaxed);
+}
+
+
+/// Set the categories allowed by the attributes of the definition being parsed, returning the previous ones
+pub fn set_allowed_warnings(kinds: &[WarningKind]) -> Vec<WarningKind> {
+    let bits = kinds.iter().fold(0, |bits, k| bits | k.bit());
+    let previous = ALLOWED_WARNINGS.swap(bits, Ordering::Relaxed);
+    WarningKind::ALL.iter().copied().filter(|k| previous & k.bit() != 0).collect()
+}
+
+
+/// Return the number of warnings reported so far
+pub fn warnings_count() -> usize {
+    WARNINGS_COUNT.load(Ordering::Relaxed)
+}
+
+
+/// Keep the next warnings instead of printing them, until [take_captured_warnings] is called
+pub fn capture_warnings() {
+    if let Ok(mut captured) = CAPTURED_WARNINGS.lock() {
+        *captured = Some(Vec::new());
+    }
+}
+
+
+/// Return the warnings kept since [capture_warnings] was called, and print the next ones again
+pub fn take_captured_warnings() -> Vec<Warning> {
+    match CAPTURED_WARNINGS.lock() {
+        Ok(mut captured) => captured.take().unwrap_or_default(),
+        Err(_) => Vec::new()
+    }
+}
+
+
+
+
 /// Represents a warning returned by the interpreter at build time.  
-/// It is a combinaison of a message and an optional position (= with tokens in a source file).
+/// It is a combinaison of a category, a message and an optional position (= with tokens in a source file).
+#[derive(Debug, Clone)]
 pub struct Warning {
+    pub kind: WarningKind,
     pub text: String,
     pub position: Option<Position>
 }
 
 impl Warning {
-    /// Create a new Warning with the given message and position
-    pub fn new(text: String, position: Option<Position>) -> Warning {
-        Warning {
-            text: text,
-            position: position
-        }
+    /// Create a new Warning with the given category, message and position
+    pub fn new(kind: WarningKind, text: String, position: Option<Position>) -> Warning {
+        Warning {kind, text, position}
     }
 
-    /// Print the warning to the standard output
-    pub fn warn(&self) {
-        let mut max_n = match &self.position {
-            None => 0,
-            Some(n) => n.line.to_string().len() + 2
+    /// Return the warning as a JSON diagnostic. Denied warnings are errors
+    pub fn to_json(&self) -> Json {
+        let severity = match DENY_WARNINGS.load(Ordering::Relaxed) {
+            true => "error",
+            false => "warning"
         };
-        if max_n < 5 {max_n = 5}
 
-        let mut raw_space = String::new();
-        for _ in 0..max_n {raw_space.push(' ')}
+        json!({
+            "severity": severity,
+            "kind": self.kind.get_name(),
+            "code": null,
+            "message": self.text,
+            "span": self.position.as_ref().map(position_to_json),
+            "trace": self.position.iter().map(position_to_json).collect::<Vec<Json>>(),
+            "call_stack": [],
+        })
+    }
+
+
+    /// Print the warning to the standard error, unless its category is disabled or allowed
+    pub fn warn(&self) {
+        let silenced = DISABLED_WARNINGS.load(Ordering::Relaxed) | ALLOWED_WARNINGS.load(Ordering::Relaxed);
+        if silenced & self.kind.bit() != 0 {return}
+
+        WARNINGS_COUNT.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut captured) = CAPTURED_WARNINGS.lock() {
+            if let Some(warnings) = captured.as_mut() {
+                warnings.push(self.clone());
+                return
+            }
+        }
+
+        if is_json_format() {
+            eprintln!("{}", self.to_json());
+            return
+        }
+
+        // denied warnings are printed like errors
+        let (color_code, accent, title) = match DENY_WARNINGS.load(Ordering::Relaxed) {
+            true => (color(RED), color(LIGHT_RED), "Denied warning"),
+            false => (color(YELLOW), color(LIGHT_YELLOW), "Warning"),
+        };
+        let reset = color(RESET);
+        let header = format!("{accent}{title} [{}]: {}{reset}", self.kind.get_name(), self.text);
 
         match &self.position {
-            None => println!("\x1b[93mWarning: {}\x1b[0m", self.text),
+            None => eprintln!("{header}"),
 
             Some(p) => {
-                let filepath = std::path::Path::new(&p.filename);
-                let file_string = std::fs::read_to_string(filepath).expect(format!("Unable to read file {:?}", filepath.as_os_str()).as_str());
-                let lines: Vec<&str> = file_string.split('\n').collect();
-
-                let nb_space_before_line_n = max_n - ((p.line + 1).to_string().len() + 1);
-
-                let mut spaces = String::new();
-                for _ in 0..nb_space_before_line_n {spaces.push(' ')}
-
-                println!("\x1b[93mWarning: {}\x1b[0m", self.text);
-                
-                println!("\x1b[33m{raw_space}|\x1b[0m");
-                println!("\x1b[33m{}{} | \x1b[0m {}", spaces, p.line + 1, lines[p.line]);
-                print!("\x1b[33m{raw_space}| \x1b[93m");
-                for _ in 0..p.first_column + 1 {print!(" ")}
-                
-                match p.last_column {
-                    Some(n) => for _ in 0..(n - p.first_column + 1) {print!("^")},
-                    None => print!("^")
-                }
-
-                println!("\x1b[90m         ({}:{})\x1b[0m", p.filename, p.line + 1);
-                
-                println!("\x1b[0m\n");
+                let raw_space = gutter_space(Some(p.last_line + 1));
+
+                eprintln!("{header}");
+                eprintln!("{color_code}{raw_space}|{reset}");
+
+                print_excerpt(p, &raw_space, color_code, accent);
+
+                eprintln!("{reset}\n");
             }
         }
     }
@@ -225,6 +558,77 @@ impl Warning {
 
 
 
+/// Maximum number of source lines printed for a multi-line position. The lines in the middle of longer spans are elided
+const MAX_EXCERPT_LINES: usize = 6;
+
+
+/// Return the blank space at the left of the excerpts, wide enough to fit the given highest line number
+fn gutter_space(max_line_n: Option<usize>) -> String {
+    let width = match max_line_n {
+        None => 0,
+        Some(n) => n.to_string().len() + 2
+    };
+
+    " ".repeat(width.max(5))
+}
+
+
+/// Print the source lines covered by the position, with their number in the gutter and carets under the covered text.
+/// The gutter is printed with the given color, the carets with the accent color.
+/// If the source can't be found, only the location is printed
+fn print_excerpt(p: &Position, raw_space: &str, color_code: &str, accent: &str) {
+    let (grey, reset) = (color(GREY), color(RESET));
+
+    let location = match p.is_multiline() {
+        true => format!("{grey}         ({}:{}-{}){reset}", p.filename, p.line + 1, p.last_line + 1),
+        false => format!("{grey}         ({}:{}){reset}", p.filename, p.line + 1),
+    };
+
+    let file_string = match sources::get(&p.filename) {
+        Some(s) => s,
+        None => {
+            eprintln!("{color_code}{raw_space}|{location}");
+            return
+        }
+    };
+    let lines: Vec<&str> = file_string.split('\n').collect();
+
+    let number_width = raw_space.len() - 1;
+    let nb_lines = p.last_line - p.line + 1;
+
+    for line_n in p.line..=p.last_line {
+        // only the start and the end of long spans are printed
+        if nb_lines > MAX_EXCERPT_LINES && line_n >= p.line + MAX_EXCERPT_LINES / 2 && line_n + MAX_EXCERPT_LINES / 2 <= p.last_line {
+            if line_n == p.line + MAX_EXCERPT_LINES / 2 {eprintln!("{color_code}{:>number_width$} |{reset}", "...");}
+            continue
+        }
+
+        let text = lines.get(line_n).copied().unwrap_or("");
+        eprintln!("{color_code}{:>number_width$} |{reset} {}", line_n + 1, text);
+
+        // the carets start at the first column on the first line, at the indentation on the next ones,
+        // and end at the last column on the last line
+        let first = match line_n == p.line {
+            true => p.first_column,
+            false => text.len() - text.trim_start().len()
+        };
+        let nb_carets = match (line_n == p.last_line, p.last_column) {
+            (true, Some(n)) => n + 1 - first.min(n),
+            (true, None) => text.len().saturating_sub(first).max(1),
+            (false, _) => text.trim_end().len().saturating_sub(first).max(1),
+        };
+
+        eprint!("{color_code}{raw_space}| {accent}{}{}", " ".repeat(first), "^".repeat(nb_carets));
+
+        if line_n == p.last_line {eprintln!("{location}")}
+        else {eprintln!("{reset}")}
+    }
+}
+
+
+
+
+
 /// Evaluate the expression if it returns Result<_, Error>.  
 /// if it returns an Error, add the given position to it and propagate it
 #[macro_export]
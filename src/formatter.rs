@@ -0,0 +1,424 @@
+//! Formatter printing a source file in the canonical style: indentation of 4 spaces, one statement per line,
+//! a semicolon after each statement and single spaces between the tokens of an expression. The comments are kept.
+//! The extent of each statement and type is found with the parser, so a file with syntax errors is not formatted.
+
+use crate::lexer::{self, Token, TokenStream, Separator, Keyword, Comments};
+use crate::parser::{self, attribute, expression, statement, types};
+use crate::position::Position;
+use crate::errors::Error;
+use crate::sloth::program::SlothProgram;
+
+
+
+const INDENT: &str = "    ";
+
+// maximum number of consecutive blank lines kept between definitions, and between the statements of a block
+const MAX_BLANK_LINES: usize = 2;
+const MAX_BLOCK_BLANK_LINES: usize = 1;
+
+
+
+struct Formatter {
+    stream: TokenStream,
+    comments: Comments,
+    next_comment: usize,
+    program: SlothProgram,          // filled by the parser when looking for the end of the statements, then dropped
+
+    output: String,
+    line: String,                   // current line, without its indentation
+    indent: usize,                  // indentation level of the current line
+    previous: Option<(Token, Position)>,    // last token written on the current line
+    last_line: Option<usize>,       // source line of the last token or comment written
+}
+
+
+
+/// Return whether a space is written between the two tokens.
+/// A colon is followed by a space only if it was in the source, as 'geo:Point' is different from 'geo: Point'
+fn needs_space(previous: &(Token, Position), next: &(Token, Position)) -> bool {
+    match (&previous.0, &next.0) {
+        (Token::Separator(Separator::OpenParenthesis | Separator::OpenSquareBracket | Separator::Period | Separator::Tilde), _) => false,
+        (_, Token::Separator(Separator::CloseParenthesis | Separator::CloseSquareBracket | Separator::SemiColon | Separator::Period | Separator::Colon)) => false,
+        (Token::Separator(Separator::OpenBracket), Token::Separator(Separator::CloseBracket)) => false,
+        (Token::Separator(Separator::Colon), _) => {
+            previous.1.line != next.1.line || previous.1.last_column.map(|c| c + 1) != Some(next.1.first_column)
+        },
+
+        // function calls and bracket accesses
+        (Token::Identifier(_), Token::Separator(Separator::OpenParenthesis)) => false,
+        (Token::Identifier(_) | Token::Literal(_) | Token::Separator(Separator::CloseParenthesis | Separator::CloseSquareBracket), Token::Separator(Separator::OpenSquareBracket)) => false,
+        _ => true
+    }
+}
+
+
+
+
+impl Formatter {
+    fn new(mut stream: TokenStream) -> Formatter {
+        let comments = std::mem::take(&mut stream.comments);
+        let program = SlothProgram::new(stream.filename.clone(), false, Vec::new());
+
+        Formatter {
+            stream,
+            comments,
+            next_comment: 0,
+            program,
+            output: String::new(),
+            line: String::new(),
+            indent: 0,
+            previous: None,
+            last_line: None,
+        }
+    }
+
+
+    /// Return the current token, or an error expecting the given element at the end of the file
+    fn current(&self, expected: &str) -> Result<(Token, Position), Error> {
+        match self.stream.current() {
+            Some(t) => Ok(t),
+            None => Err(parser::wrong_token(None, expected))
+        }
+    }
+
+
+    /// Write the current line to the output
+    fn newline(&mut self) {
+        if !self.line.is_empty() {
+            self.output.push_str(&INDENT.repeat(self.indent));
+            self.output.push_str(self.line.trim_end());
+            self.output.push('\n');
+            self.line.clear();
+        }
+        self.previous = None;
+    }
+
+
+    /// Write the blank lines found in the source before the given line, up to max
+    fn blank_lines(&mut self, line: usize, max: usize) {
+        if let Some(last) = self.last_line {
+            let count = line.saturating_sub(last + 1).min(max);
+            self.output.push_str(&"\n".repeat(count));
+        }
+    }
+
+
+    /// Write the comments placed before the given position (all the remaining ones if None).
+    /// A comment following a token on the same line stays after it, the others are written on their own line
+    /// with the given indentation. Return whether a comment was written
+    fn flush_comments(&mut self, until: Option<&Position>, indent: usize, max_blank: usize) -> bool {
+        let mut written = false;
+
+        while let Some((text, pos)) = self.comments.get(self.next_comment).cloned() {
+            if let Some(u) = until {
+                if (pos.line, pos.first_column) >= (u.line, u.first_column) {break}
+            }
+
+            if self.line.is_empty() || self.last_line != Some(pos.line) {
+                self.newline();
+                self.blank_lines(pos.line, max_blank);
+                self.indent = indent;
+            }
+            else {self.line.push(' ')}
+
+            // nothing can follow a comment on its line
+            self.line.push_str(&text);
+            self.newline();
+
+            self.last_line = Some(pos.line);
+            self.next_comment += 1;
+            written = true;
+        }
+
+        written
+    }
+
+
+    /// Start a new line with the given indentation for the current token, after the comments placed before it
+    fn start_line(&mut self, indent: usize, max_blank: usize) {
+        match self.stream.current() {
+            Some((_, pos)) => {
+                self.flush_comments(Some(&pos), indent, max_blank);
+                self.newline();
+                self.blank_lines(pos.line, max_blank);
+            },
+            None => self.newline()
+        }
+        self.indent = indent;
+    }
+
+
+    /// Write the current token and go to the next one. If a comment forces a new line before it,
+    /// the line is indented with the continuation indentation
+    fn write_token(&mut self, continuation: usize) {
+        if let Some((token, pos)) = self.stream.current() {
+            if self.flush_comments(Some(&pos), continuation, 0) {self.indent = continuation}
+
+            if let Some(previous) = &self.previous {
+                if needs_space(previous, &(token.clone(), pos.clone())) {self.line.push(' ')}
+            }
+            self.line.push_str(&token.original_string());
+
+            self.last_line = Some(pos.last_line);
+            self.previous = Some((token, pos));
+            self.stream.next();
+        }
+    }
+
+
+    /// Write the current token if it matches, or return an error
+    fn write_expected(&mut self, matches: fn(&Token) -> bool, expected: &str) -> Result<(), Error> {
+        let (token, pos) = self.current(expected)?;
+        if !matches(&token) {return Err(parser::wrong_token(Some((token, pos)), expected))}
+
+        self.write_token(self.indent + 1);
+        Ok(())
+    }
+
+
+    /// Write the colon following the name of a field or a function, always followed by a space
+    fn write_label_colon(&mut self) -> Result<(), Error> {
+        self.write_expected(|t| matches!(t, Token::Separator(Separator::Colon)), "':'")?;
+        self.line.push(' ');
+        self.previous = None;
+        Ok(())
+    }
+
+
+    /// Write the semicolon ending a statement or a definition, adding it if it is missing
+    fn write_semicolon(&mut self) {
+        match self.stream.current() {
+            Some((Token::Separator(Separator::SemiColon), _)) => self.write_token(self.indent + 1),
+            _ => {
+                if let Some((_, pos)) = self.previous.clone() {
+                    self.line.push(';');
+                    self.previous = Some((Token::Separator(Separator::SemiColon), pos));
+                }
+            }
+        }
+    }
+
+
+    /// Write the tokens until the given index. The line breaks of the source are kept inside parenthesis and
+    /// square brackets, with one more level of indentation for each of them. The blocks are formatted
+    fn write_range(&mut self, end: usize) -> Result<(), Error> {
+        let base = self.indent;
+        let mut depth: usize = 0;
+
+        while self.stream.index() < end {
+            let (token, pos) = self.current("token")?;
+
+            let closing = match token {
+                Token::Separator(Separator::OpenBracket) => {
+                    self.write_block()?;
+                    continue
+                },
+                Token::Separator(Separator::CloseParenthesis | Separator::CloseSquareBracket) => {
+                    depth = depth.saturating_sub(1);
+                    true
+                },
+                _ => false
+            };
+
+            let on_new_line = self.last_line.is_some_and(|l| pos.line > l);
+            if on_new_line && !self.line.is_empty() && (depth > 0 || closing) {
+                self.newline();
+                self.indent = base + depth;
+            }
+            self.write_token(base + depth.max(1));
+
+            if let Token::Separator(Separator::OpenParenthesis | Separator::OpenSquareBracket) = token {depth += 1}
+        }
+
+        Ok(())
+    }
+
+
+    /// Write the type starting at the current token
+    fn write_type(&mut self) -> Result<(), Error> {
+        let start = self.stream.index();
+        types::parse_type(&mut self.stream, &mut self.program, &None, false)?;
+        let end = self.stream.index();
+        self.stream.rewind(start);
+
+        self.write_range(end)
+    }
+
+
+    /// Write the statement starting at the current token, with its semicolon
+    fn write_statement(&mut self) -> Result<(), Error> {
+        let start = self.stream.index();
+        statement::parse_unterminated_statement(&mut self.stream, &mut self.program, false)?;
+        let end = self.stream.index();
+        self.stream.rewind(start);
+
+        self.write_range(end)?;
+        self.write_semicolon();
+        Ok(())
+    }
+
+
+    /// Write the block starting at the current '{', with one statement per line
+    fn write_block(&mut self) -> Result<(), Error> {
+        let outer = self.indent;
+        self.write_expected(|t| matches!(t, Token::Separator(Separator::OpenBracket)), "'{'")?;
+
+        let mut is_empty = true;
+        while !matches!(self.current("'}'")?.0, Token::Separator(Separator::CloseBracket)) {
+            self.start_line(outer + 1, MAX_BLOCK_BLANK_LINES);
+            self.write_statement()?;
+            is_empty = false;
+        }
+
+        self.close_block(outer, is_empty);
+        Ok(())
+    }
+
+
+    /// Write the fields of the structure starting at the current '{', one per line
+    fn write_fields(&mut self) -> Result<(), Error> {
+        let outer = self.indent;
+        self.write_expected(|t| matches!(t, Token::Separator(Separator::OpenBracket)), "'{'")?;
+
+        let mut is_empty = true;
+        while !matches!(self.current("'}'")?.0, Token::Separator(Separator::CloseBracket)) {
+            self.start_line(outer + 1, MAX_BLOCK_BLANK_LINES);
+
+            if let Some((Token::Keyword(Keyword::Pub), _)) = self.stream.current() {self.write_token(outer + 2)}
+            self.write_expected(|t| matches!(t, Token::Identifier(_)), "field name or '}'")?;
+            self.write_label_colon()?;
+            self.write_type()?;
+            self.write_semicolon();
+            is_empty = false;
+        }
+
+        self.close_block(outer, is_empty);
+        Ok(())
+    }
+
+
+    /// Write the '}' closing a block or a structure, on its own line unless the block is empty
+    fn close_block(&mut self, outer: usize, is_empty: bool) {
+        if let Some((_, pos)) = self.stream.current() {
+            let has_comments = self.comments.get(self.next_comment).is_some_and(|(_, p)| (p.line, p.first_column) < (pos.line, pos.first_column));
+
+            if !is_empty || has_comments {
+                self.flush_comments(Some(&pos), outer + 1, MAX_BLOCK_BLANK_LINES);
+                self.newline();
+                self.indent = outer;
+            }
+        }
+        self.write_token(outer + 1);
+    }
+
+
+    /// Write the top-level definition starting at the current token, with its attributes
+    fn write_definition(&mut self) -> Result<(), Error> {
+        // each attribute on its own line
+        while attribute::is_attribute_start(&mut self.stream) {
+            while !matches!(self.current("')'")?.0, Token::Separator(Separator::CloseParenthesis)) {
+                self.write_token(1);
+            }
+            self.write_token(1);
+            self.start_line(0, 0);
+        }
+
+        if let Some((Token::Keyword(Keyword::Pub), _)) = self.stream.current() {
+            self.write_token(1);
+            let (token, pos) = self.current("'define' or 'structure'")?;
+            if !matches!(token, Token::Keyword(Keyword::Define | Keyword::Structure)) {
+                return Err(parser::wrong_token(Some((token, pos)), "'define' or 'structure'"))
+            }
+        }
+
+        let (token, pos) = self.current("definition")?;
+        match token {
+            Token::Keyword(Keyword::Builtin) => {
+                self.write_token(1);
+                self.write_expected(|t| matches!(t, Token::Identifier(_)), "module")?;
+                if let Some((Token::Separator(Separator::Colon), _)) = self.stream.current() {
+                    self.write_token(1);
+                    self.write_expected(|t| matches!(t, Token::Identifier(_)), "function or structure name")?;
+                }
+                self.write_semicolon();
+            },
+
+            Token::Keyword(Keyword::Import) => {
+                self.write_token(1);
+                self.write_expected(|t| matches!(t, Token::Literal(_) | Token::Identifier(_)), "file or package name")?;
+                self.write_semicolon();
+            },
+
+            Token::Keyword(Keyword::Static) => {
+                self.write_token(1);
+                self.write_expected(|t| matches!(t, Token::Identifier(_)), "static expr. name")?;
+                self.write_expected(|t| matches!(t, Token::Keyword(Keyword::Equal)), "'='")?;
+
+                let start = self.stream.index();
+                expression::parse_expression(&mut self.stream, &mut self.program, false, None)?;
+                let end = self.stream.index();
+                self.stream.rewind(start);
+
+                self.write_range(end)?;
+                self.write_semicolon();
+            },
+
+            Token::Keyword(Keyword::Structure) => {
+                self.write_token(1);
+                self.write_expected(|t| matches!(t, Token::Identifier(_)), "structure name")?;
+                self.write_fields()?;
+            },
+
+            Token::Keyword(Keyword::Define) => {
+                self.write_token(1);
+                self.write_expected(|t| matches!(t, Token::Identifier(_)), "function")?;
+                if let Some((Token::Keyword(Keyword::For), _)) = self.stream.current() {
+                    self.write_token(1);
+                    self.write_type()?;
+                }
+                self.write_label_colon()?;
+
+                // input types until '->'
+                while !matches!(self.current("'->'")?.0, Token::Keyword(Keyword::LeftArrow)) {
+                    if let Some((Token::Separator(Separator::Tilde), _)) = self.stream.current() {self.write_token(1)}
+                    self.write_type()?;
+                }
+                self.write_token(1);
+
+                self.write_type()?;
+                self.write_block()?;
+            },
+
+            t => return Err(parser::wrong_token(Some((t, pos)), "'builtin', 'import', 'static', 'pub', 'structure' or 'define'"))
+        }
+
+        Ok(())
+    }
+
+
+    /// Write the whole file, returning the formatted text
+    fn write_file(mut self) -> Result<String, Vec<Error>> {
+        while self.stream.current().is_some() {
+            self.start_line(0, MAX_BLANK_LINES);
+            if let Err(e) = self.write_definition() {return Err(vec![e])}
+        }
+
+        self.newline();
+        self.flush_comments(None, 0, MAX_BLANK_LINES);
+
+        // errors in the nested blocks are recovered by the parser
+        let errors = self.program.take_parse_errors();
+        if !errors.is_empty() {return Err(errors)}
+
+        Ok(self.output)
+    }
+}
+
+
+
+
+/// Return the formatted text of the given file
+pub fn format_file(filename: &str) -> Result<String, Vec<Error>> {
+    let stream = lexer::get_token_stream(filename).map_err(|e| vec![e])?;
+    Formatter::new(stream).write_file()
+}
@@ -1,4 +1,4 @@
-pub const KEYWORDS: [&str; 11] = ["define", "->", "=", "if", "while", "builtin", "for", "new", "import", "structure", "static"];
+pub const KEYWORDS: [&str; 12] = ["define", "->", "=", "if", "while", "builtin", "for", "new", "import", "structure", "static", "pub"];
 
 
 #[derive(Clone, Debug, PartialEq)]
@@ -8,6 +8,7 @@ pub enum Keyword {
     Static,
     Structure,
     Define,
+    Pub,
     For,
     LeftArrow,
     New,
@@ -25,6 +26,7 @@ impl Keyword {
             Keyword::Static => "static",
             Keyword::Structure => "structure",
             Keyword::Define => "define",
+            Keyword::Pub => "pub",
             Keyword::For => "for",
             Keyword::LeftArrow => "->",
             Keyword::New => "new",
@@ -42,6 +44,7 @@ impl Keyword {
             "static" => Keyword::Static,
             "structure" => Keyword::Structure,
             "define" => Keyword::Define,
+            "pub" => Keyword::Pub,
             "for" => Keyword::For,
             "->" => Keyword::LeftArrow,
             "new" => Keyword::New,
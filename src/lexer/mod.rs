@@ -9,7 +9,7 @@ pub mod separator;
 pub mod keyword;
 pub mod operator;
 
-pub use tokenstream::TokenStream;
+pub use tokenstream::{TokenStream, Comments};
 pub use token::Token;
 pub use separator::Separator;
 pub use keyword::Keyword;
@@ -30,15 +30,26 @@ pub const COMMENT_STR: [char; 2] = ['/', '/'];
 
 /// Generate a TokenStream from the given file
 pub fn get_token_stream(filename: &str) -> Result<TokenStream, Error> {
-    let tokens = tokeniser::from_file(filename)?;
+    let (tokens, comments) = tokeniser::from_file(filename)?;
     let length = tokens.len();
 
     let stream = TokenStream::new(
         filename.to_string(),
         tokens,
+        comments,
         length,
         0
     );
 
     Ok(stream)
+}
+
+
+/// Generate a TokenStream from the given source text, which is not read from a file.
+/// The name of the source is used in the positions of the tokens
+pub fn get_token_stream_from_string(name: &str, source: &str) -> Result<TokenStream, Error> {
+    let (tokens, comments) = tokeniser::from_string(name, source)?;
+    let length = tokens.len();
+
+    Ok(TokenStream::new(name.to_string(), tokens, comments, length, 0))
 }
\ No newline at end of file
@@ -1,28 +1,50 @@
 use crate::position::Position;
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
+use crate::sources;
 use super::token::Token;
+use super::tokenstream::Comments;
 
 
 
 
 
-/// Convert the given file into a list of Tokens
-pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
+/// Convert the given file into a list of Tokens, and the list of its comments.
+/// If an in-memory source was registered with this name, it is used instead
+pub fn from_file(filename: &str) -> Result<(Vec<(Token, Position)>, Comments), Error> {
+    if let Some(text) = sources::get_in_memory(filename) {
+        return tokenise(filename, &text)
+    }
+
     let filepath = std::path::Path::new(filename);
 
     if !filepath.exists() {
         let err_msg = format!("File {:?} does not exists", filepath.as_os_str());
-        return Err(Error::new(ErrMsg::FileError(err_msg), None));
+        return Err(Error::new(ErrMsg::FileError(err_msg), None).with_code(ErrCode::S0301));
     }
 
-    let mut tokens: Vec<(Token, Position)> = Vec::new();
-
-
     let file_string = match std::fs::read_to_string(filepath) {
         Ok(v) => v,
-        Err(e) => return Err(Error::new(ErrMsg::FileError(e.to_string()), None))
+        Err(e) => return Err(Error::new(ErrMsg::FileError(e.to_string()), None).with_code(ErrCode::S0301))
     };
 
+    from_string(filename, &file_string)
+}
+
+
+/// Convert the given source text into a list of Tokens, and the list of its comments. The filename is the one given to the positions of the tokens.
+/// The source is registered so the diagnostics can quote it, even if it is not on disk
+pub fn from_string(filename: &str, file_string: &str) -> Result<(Vec<(Token, Position)>, Comments), Error> {
+    sources::register(filename, file_string);
+    tokenise(filename, file_string)
+}
+
+
+/// Convert the given source text into a list of Tokens and a list of comments, without registering it
+pub fn tokenise(filename: &str, file_string: &str) -> Result<(Vec<(Token, Position)>, Comments), Error> {
+    let mut tokens: Vec<(Token, Position)> = Vec::new();
+    let mut comments: Comments = Vec::new();
+
     let lines = file_string.split('\n');
 
     // parse each line one by one, as a token can't be between 2 lines
@@ -61,13 +83,14 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                 let position = Position {
                     filename: filename.to_string(),
                     line: string_start.0,
+                    last_line: string_start.0,
                     first_column: string_start.1,
                     last_column: Some(c_index)
                 };
 
                 match Token::from_str(&string_buffer) {
                     Ok(s) => tokens.push((s, position)),
-                    Err(e) => return Err(Error::new(ErrMsg::SyntaxError(e), Some(position))),
+                    Err(e) => return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)).with_code(ErrCode::S0101)),
                 }
 
                 string_buffer.clear();
@@ -100,6 +123,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                         let position = Position {
                             filename: filename.to_string(),
                             line: token_start.0,
+                            last_line: token_start.0,
                             first_column: token_start.1,
                             last_column: Some(c_index - 1)
                         };
@@ -107,13 +131,24 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                         match Token::from_str(&token_buffer) {
                             Ok(s) => tokens.push((s, position)),
                             Err(e) => {
-                                return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)));
+                                return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)).with_code(ErrCode::S0101));
                             },
                         }
 
                         token_buffer.clear();
                     }
 
+                    // keep the comment as trivia, for the formatter
+                    let comment: String = line.chars().skip(c_index).collect();
+                    let position = Position {
+                        filename: filename.to_string(),
+                        line: line_index,
+                        last_line: line_index,
+                        first_column: c_index,
+                        last_column: Some(line.chars().count() - 1)
+                    };
+                    comments.push((comment.trim_end().to_string(), position));
+
                     line_index += 1; // increment line_index here as "continue 'lines" won't call the last statement of the loop 'lines' 
                     continue 'lines;
                 }
@@ -149,6 +184,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                                 let op_pos = Position {
                                     filename: filename.to_string(),
                                     line: token_start.0,
+                                    last_line: token_start.0,
                                     first_column: token_start.1,
                                     last_column: Some(token_start.1 + op.len())
                                 };
@@ -160,7 +196,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                                 match Token::from_str(op) {
                                     Ok(s) => tokens.push((s, op_pos)),
                                     Err(e) => {
-                                        return Err(Error::new(ErrMsg::SyntaxError(e), Some(op_pos)));
+                                        return Err(Error::new(ErrMsg::SyntaxError(e), Some(op_pos)).with_code(ErrCode::S0101));
                                     },
                                 };
                             }
@@ -173,6 +209,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                         let position = Position {
                             filename: filename.to_string(),
                             line: token_start.0,
+                            last_line: token_start.0,
                             first_column: token_start.1,
                             last_column: Some(c_index - 1)
                         };
@@ -180,7 +217,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                         match Token::from_str(&token_buffer) {
                             Ok(s) => tokens.push((s, position)),
                             Err(e) => {
-                                return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)));
+                                return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)).with_code(ErrCode::S0101));
                             },
                         };
 
@@ -192,6 +229,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                         let position = Position {
                             filename: filename.to_string(),
                             line: line_index,
+                            last_line: line_index,
                             first_column: c_index,
                             last_column: Some(c_index)
                         };
@@ -199,7 +237,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
                         match Token::from_str(&c.to_string()) {
                             Ok(s) => tokens.push((s, position)),
                             Err(e) => {
-                                return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)));
+                                return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)).with_code(ErrCode::S0101));
                             },
                         };
 
@@ -229,6 +267,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
             let position = Position {
                 filename: filename.to_string(),
                 line: token_start.0,
+                last_line: token_start.0,
                 first_column: token_start.1,
                 last_column: Some(line.len() - 1)
             };
@@ -236,7 +275,7 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
             match Token::from_str(&token_buffer) {
                 Ok(s) => tokens.push((s, position)),
                 Err(e) => {
-                    return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)));
+                    return Err(Error::new(ErrMsg::SyntaxError(e), Some(position)).with_code(ErrCode::S0101));
                 },
             }
 
@@ -249,5 +288,5 @@ pub fn from_file(filename: &str) -> Result<Vec<(Token, Position)>, Error> {
     }
 
 
-    Ok(tokens)
+    Ok((tokens, comments))
 }
\ No newline at end of file
@@ -1,19 +1,25 @@
 use super::token::Token;
 use crate::position::Position;
 
+
+/// Comments of a file with their position. They are kept apart from the tokens, as the parser ignores them
+pub type Comments = Vec<(String, Position)>;
+
+
 /// Iterator-like structure listing each token of a file along with their position
 #[derive(Clone)]
 pub struct TokenStream {
     pub filename: String,
     pub tokens: Vec<(Token, Position)>,
+    pub comments: Comments,
     nb_tokens: usize,
     current: usize,
 }
 
 
 impl TokenStream {
-    pub fn new(filename: String, tokens: Vec<(Token, Position)>, nb_tokens: usize, current: usize) -> TokenStream {
-        TokenStream { filename, tokens, nb_tokens, current }
+    pub fn new(filename: String, tokens: Vec<(Token, Position)>, comments: Comments, nb_tokens: usize, current: usize) -> TokenStream {
+        TokenStream { filename, tokens, comments, nb_tokens, current }
     }
 
     /// return the nth value of the iterator
@@ -22,6 +28,11 @@ impl TokenStream {
         else {Some(self.tokens[index].clone())}
     }
 
+    /// return the index of the current value of the iterator
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
     /// return current value of the iterator
     pub fn current(&self) -> Option<(Token, Position)> {
         self.nth(self.current)
@@ -38,6 +49,11 @@ impl TokenStream {
         self.nth(self.current)
     }
 
+    /// go back to the given index, returned by 'index()'
+    pub fn rewind(&mut self, index: usize) {
+        self.current = index;
+    }
+
     /// return the nth next value without switching to it
     pub fn peek(&mut self, nth: isize) -> Option<(Token, Position)> {
         if nth < 0 {self.nth(self.current - (-nth as usize))}
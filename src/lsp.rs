@@ -0,0 +1,462 @@
+//! Language server speaking the Language Server Protocol over the standard input and output.
+//! Each open document is built as the main file of a program and checked: the errors and warnings are published
+//! as diagnostics, and the last program built successfully is used for the hover, go-to-definition and completion.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value as Json};
+
+use crate::builtins;
+use crate::checker;
+use crate::errors::{self, Error, Warning};
+use crate::lexer::{tokeniser, Keyword, Token};
+use crate::lexer::keyword::KEYWORDS;
+use crate::manifest::{self, Manifest};
+use crate::parser;
+use crate::position::Position;
+use crate::sloth::program::{SlothProgram, DEFAULT_MAX_ERRORS};
+use crate::sources;
+
+
+
+// error code of JSON-RPC
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// kinds of completion items and severities of diagnostics, as numbered by the protocol
+const FUNCTION_ITEM: u32 = 3;
+const KEYWORD_ITEM: u32 = 14;
+const STRUCT_ITEM: u32 = 22;
+const ERROR_SEVERITY: u32 = 1;
+const WARNING_SEVERITY: u32 = 2;
+
+
+
+/// Read a message from the client: a JSON body preceded by a 'Content-Length' header.
+/// Return None at the end of the input. A body which is not valid JSON is returned as null
+pub fn read_message(reader: &mut impl BufRead) -> io::Result<Option<Json>> {
+    let mut length = None;
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {return Ok(None)}
+
+        let line = line.trim_end();
+        if line.is_empty() {break}
+        if let Some(v) = line.strip_prefix("Content-Length:") {length = v.trim().parse::<usize>().ok()}
+    }
+
+    let length = match length {
+        Some(l) => l,
+        None => return Err(io::Error::new(io::ErrorKind::InvalidData, "missing 'Content-Length' header"))
+    };
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).unwrap_or(Json::Null)))
+}
+
+
+/// Write a message to the client, with its 'Content-Length' header
+pub fn write_message(writer: &mut impl Write, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    writer.flush()
+}
+
+
+
+
+/// Return the path of a 'file://' URI
+fn uri_to_path(uri: &str) -> String {
+    let encoded = uri.strip_prefix("file://").unwrap_or(uri).as_bytes();
+    let mut decoded = Vec::new();
+
+    let mut i = 0;
+    while i < encoded.len() {
+        let escaped = match (encoded[i], encoded.get(i + 1..i + 3)) {
+            (b'%', Some(hex)) => std::str::from_utf8(hex).ok().and_then(|h| u8::from_str_radix(h, 16).ok()),
+            _ => None
+        };
+
+        match escaped {
+            Some(c) => {decoded.push(c); i += 3},
+            None => {decoded.push(encoded[i]); i += 1}
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+
+/// Return the 'file://' URI of a path, relative to the current directory if it is not absolute
+fn path_to_uri(path: &str) -> String {
+    let path = match std::env::current_dir() {
+        Ok(dir) => dir.join(path),
+        Err(_) => PathBuf::from(path)
+    };
+    format!("file://{}", path.to_string_lossy().replace('%', "%25").replace(' ', "%20"))
+}
+
+
+/// Return the range of the protocol corresponding to the position (lines and characters start at 0, the end is excluded)
+fn position_to_range(position: &Position) -> Json {
+    let last_column = position.last_column.unwrap_or(position.first_column);
+    json!({
+        "start": {"line": position.line, "character": position.first_column},
+        "end": {"line": position.last_line, "character": last_column + 1},
+    })
+}
+
+
+/// Return the packages declared in the manifest of the project containing the file, if any
+pub fn get_packages(path: &str) -> HashMap<String, PathBuf> {
+    for dir in Path::new(path).ancestors().skip(1) {
+        if dir.join(manifest::MANIFEST_NAME).exists() {
+            return Manifest::from_dir(dir).and_then(|m| m.resolve_dependencies()).unwrap_or_default()
+        }
+    }
+    HashMap::new()
+}
+
+
+/// Return the identifier at the given line and character of the source, if any
+fn identifier_at(path: &str, source: &str, line: usize, character: usize) -> Option<String> {
+    let (tokens, _) = tokeniser::tokenise(path, source).ok()?;
+
+    tokens.into_iter().find_map(|(t, p)| match t {
+        Token::Identifier(n) if p.line == line && p.first_column <= character && character <= p.last_column.unwrap_or(p.first_column) + 1 => Some(n),
+        _ => None
+    })
+}
+
+
+
+
+/// Open document, with the last program built from it without errors
+struct Document {
+    text: String,
+    program: Option<SlothProgram>,
+}
+
+
+struct Server {
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+}
+
+
+impl Server {
+    fn new() -> Server {
+        Server {documents: HashMap::new(), shutdown: false}
+    }
+
+
+    /// Build and check the program of the document, returning its diagnostics
+    fn analyse(&mut self, path: &str) -> Vec<Json> {
+        let text = match self.documents.get(path) {
+            Some(d) => d.text.clone(),
+            None => return Vec::new()
+        };
+
+        // the parser reads the text of the document instead of the file, which may not be saved
+        sources::register_in_memory(path, &text);
+
+        errors::capture_warnings();
+        let packages = get_packages(path);
+        let filename = path.to_string();
+        let built = std::panic::catch_unwind(move || {
+            let program = parser::build_program(filename, true, true, Vec::new(), packages, DEFAULT_MAX_ERRORS).map_err(|(e, _)| e)?;
+            let errors = checker::check_program(&program);
+            Ok((program, errors))
+        });
+        let warnings = errors::take_captured_warnings();
+
+        let errors = match built {
+            Ok(Ok((program, errors))) => {
+                if let Some(d) = self.documents.get_mut(path) {d.program = Some(program)}
+                errors
+            },
+            Ok(Err(errors)) => errors,
+            Err(_) => {
+                let err_msg = "The interpreter panicked while building the program".to_string();
+                vec![Error::new(errors::ErrMsg::RustError(err_msg), None).with_code(crate::codes::ErrCode::S0901)]
+            }
+        };
+
+        let mut diagnostics: Vec<Json> = errors.iter().map(|e| Self::error_diagnostic(path, e)).collect();
+        diagnostics.extend(warnings.iter().map(|w| Self::warning_diagnostic(path, w)));
+        diagnostics
+    }
+
+
+    /// Return the diagnostic of the error. An error in another file is placed at the start of the document
+    fn error_diagnostic(path: &str, error: &Error) -> Json {
+        let (range, message) = Self::locate(path, error.position_trace.last(), error.message.to_string());
+        json!({
+            "range": range,
+            "severity": ERROR_SEVERITY,
+            "code": error.code.map(|c| c.as_str()),
+            "source": "sloth",
+            "message": message,
+        })
+    }
+
+
+    /// Return the diagnostic of the warning. A warning in another file is placed at the start of the document
+    fn warning_diagnostic(path: &str, warning: &Warning) -> Json {
+        let (range, message) = Self::locate(path, warning.position.as_ref(), warning.text.clone());
+        json!({
+            "range": range,
+            "severity": WARNING_SEVERITY,
+            "code": warning.kind.get_name(),
+            "source": "sloth",
+            "message": message,
+        })
+    }
+
+
+    /// Return the range and message of a diagnostic of the document at the given position
+    fn locate(path: &str, position: Option<&Position>, message: String) -> (Json, String) {
+        let start = json!({"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 0}});
+
+        match position {
+            Some(p) if p.filename == path => (position_to_range(p), message),
+            Some(p) => (start, format!("{} {}", message, p)),
+            None => (start, message)
+        }
+    }
+
+
+    /// Return the locations of the definitions (functions, structures and statics) with the given name,
+    /// in every source of the program
+    fn find_definitions(&self, name: &str) -> Vec<Json> {
+        let mut locations = Vec::new();
+
+        for source_name in sources::get_names() {
+            let text = match sources::get(&source_name) {
+                Some(t) => t,
+                None => continue
+            };
+            let tokens = match tokeniser::tokenise(&source_name, &text) {
+                Ok((t, _)) => t,
+                Err(_) => continue
+            };
+
+            for pair in tokens.windows(2) {
+                if let [(Token::Keyword(Keyword::Define | Keyword::Structure | Keyword::Static), _), (Token::Identifier(n), p)] = pair {
+                    if n == name {
+                        locations.push(json!({"uri": path_to_uri(&source_name), "range": position_to_range(p)}));
+                    }
+                }
+            }
+        }
+
+        locations
+    }
+
+
+    /// Return the markdown describing the functions and structures with the given name
+    fn describe(program: &SlothProgram, name: &str) -> Option<String> {
+        let mut lines = Vec::new();
+
+        let mut signatures = program.get_function_signatures();
+        signatures.retain(|s| s.name == name);
+        signatures.sort_unstable_by_key(|s| (&s.module, s.to_definition_string()));
+        for s in signatures {
+            match &s.module {
+                Some(m) => lines.push(format!("define {}:{}", m, s.to_definition_string())),
+                None => lines.push(format!("define {}", s.to_definition_string()))
+            }
+        }
+
+        for s in program.get_custom_structures() {
+            if s.signature.name != name {continue}
+
+            lines.push(format!("structure {} {{", s.signature.name));
+            for (field, t) in &s.fields {
+                lines.push(format!("    {}: {};", field, t));
+            }
+            lines.push("}".to_string());
+        }
+
+        match lines.is_empty() {
+            true => None,
+            false => Some(format!("```sloth\n{}\n```", lines.join("\n")))
+        }
+    }
+
+
+    /// Return the completion items: the keywords, the builtins of every module and the definitions of the program
+    fn completions(program: Option<&SlothProgram>) -> Vec<Json> {
+        let mut items = Vec::new();
+        let mut labels = HashSet::new();
+
+        for keyword in KEYWORDS.iter().filter(|k| k.chars().all(char::is_alphabetic)) {
+            items.push(json!({"label": keyword, "kind": KEYWORD_ITEM}));
+        }
+
+        for module in builtins::MODULES {
+            for builtin in builtins::get_module_builtins(module).unwrap_or_default() {
+                if labels.insert(builtin.to_string()) {
+                    items.push(json!({"label": builtin, "kind": FUNCTION_ITEM, "detail": format!("builtin {}", module)}));
+                }
+            }
+        }
+
+        if let Some(program) = program {
+            for f in program.get_custom_functions() {
+                if labels.insert(f.signature.name.clone()) {
+                    items.push(json!({"label": f.signature.name, "kind": FUNCTION_ITEM, "detail": f.signature.to_definition_string()}));
+                }
+            }
+            for s in program.get_custom_structures() {
+                if labels.insert(s.signature.name.clone()) {
+                    items.push(json!({"label": s.signature.name, "kind": STRUCT_ITEM}));
+                }
+            }
+        }
+
+        items
+    }
+
+
+    /// Return the path of the document and the identifier at the position given in the parameters of a request
+    fn requested_identifier(&self, params: &Json) -> Option<(String, String)> {
+        let path = uri_to_path(params["textDocument"]["uri"].as_str()?);
+        let line = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+
+        let document = self.documents.get(&path)?;
+        let name = identifier_at(&path, &document.text, line, character)?;
+        Some((path, name))
+    }
+
+
+    /// Handle a request, returning its result or an error code and message
+    fn handle_request(&mut self, method: &str, params: &Json) -> Result<Json, (i64, String)> {
+        match method {
+            "initialize" => {
+                // imports are relative to the root of the workspace, as when running 'sloth' from it
+                let root = params["rootUri"].as_str().map(uri_to_path).or(params["rootPath"].as_str().map(String::from));
+                if let Some(root) = root {
+                    let _ = std::env::set_current_dir(root);
+                }
+
+                Ok(json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "completionProvider": {"triggerCharacters": ["."]},
+                    },
+                    "serverInfo": {"name": "sloth", "version": env!("CARGO_PKG_VERSION")},
+                }))
+            },
+
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Json::Null)
+            },
+
+            "textDocument/definition" => match self.requested_identifier(params) {
+                Some((_, name)) => Ok(Json::Array(self.find_definitions(&name))),
+                None => Ok(Json::Null)
+            },
+
+            "textDocument/hover" => {
+                let description = self.requested_identifier(params).and_then(|(path, name)| {
+                    let program = self.documents.get(&path)?.program.as_ref()?;
+                    Self::describe(program, &name)
+                });
+
+                match description {
+                    Some(d) => Ok(json!({"contents": {"kind": "markdown", "value": d}})),
+                    None => Ok(Json::Null)
+                }
+            },
+
+            "textDocument/completion" => {
+                let path = params["textDocument"]["uri"].as_str().map(uri_to_path).unwrap_or_default();
+                let program = self.documents.get(&path).and_then(|d| d.program.as_ref());
+                Ok(Json::Array(Self::completions(program)))
+            },
+
+            m => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", m)))
+        }
+    }
+
+
+    /// Handle a notification, returning the diagnostics to publish for a document, if any
+    fn handle_notification(&mut self, method: &str, params: &Json) -> Option<(String, Vec<Json>)> {
+        let path = uri_to_path(params["textDocument"]["uri"].as_str()?);
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str()?.to_string();
+                self.documents.insert(path.clone(), Document {text, program: None});
+            },
+
+            // the whole text is sent on each change
+            "textDocument/didChange" => {
+                let text = params["contentChanges"].as_array()?.last()?["text"].as_str()?.to_string();
+                self.documents.get_mut(&path)?.text = text;
+            },
+
+            "textDocument/didSave" => (),
+
+            "textDocument/didClose" => {
+                self.documents.remove(&path);
+                sources::remove(&path);
+                return Some((path, Vec::new()))
+            },
+
+            _ => return None
+        }
+
+        let diagnostics = self.analyse(&path);
+        Some((path, diagnostics))
+    }
+}
+
+
+
+
+/// Run the language server until the client exits. Return whether it asked for a shutdown before
+pub fn run() -> io::Result<bool> {
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let mut stdout = io::stdout();
+    let mut server = Server::new();
+
+    while let Some(message) = read_message(&mut reader)? {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+
+        if method == "exit" {break}
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match server.handle_request(method, params) {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                    Err((code, msg)) => json!({"jsonrpc": "2.0", "id": id, "error": {"code": code, "message": msg}}),
+                };
+                write_message(&mut stdout, &response)?;
+            },
+
+            None => {
+                if let Some((path, diagnostics)) = server.handle_notification(method, params) {
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/publishDiagnostics",
+                        "params": {"uri": path_to_uri(&path), "diagnostics": diagnostics},
+                    });
+                    write_message(&mut stdout, &notification)?;
+                }
+            }
+        }
+    }
+
+    Ok(server.shutdown)
+}
@@ -7,11 +7,29 @@ mod builtins;
 mod errors;
 mod position;
 mod operations;
+mod manifest;
+mod checker;
+mod suggestions;
+mod sources;
+mod codes;
+mod repl;
+mod formatter;
+mod lsp;
+mod debugger;
+mod dap;
+mod profiler;
+mod tracer;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use manifest::Manifest;
 use sloth::program::SlothProgram;
 use sloth::value::Value;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::time::{Instant, Duration};
 
 
@@ -19,41 +37,102 @@ use std::time::{Instant, Duration};
 /// Interpreter for the Sloth Programming Language 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
+#[clap(args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Path of the .slo file to execute
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// Path of the .slo file to execute, or '-' to read it from the standard input
     #[clap(value_parser)]
-    file: String,
+    file: Option<String>,
+
+    /// Execute the given source code instead of a file. The following arguments are given to the program
+    #[clap(short = 'e', long = "eval", value_parser, value_name = "SOURCE")]
+    inline: Option<String>,
 
     /// Print the return code after execution
-    #[clap(short, long, value_parser)]
+    #[clap(short, long, value_parser, global = true)]
     code: bool,
 
     /// Compute and display the time of building and execution of the program
-    #[clap(short, long, value_parser)]
+    #[clap(short, long, value_parser, global = true)]
     time: bool,
 
+    /// Profile the function calls, and print the calls and time of each function after the execution
+    #[clap(long, value_parser, global = true)]
+    profile: bool,
+
+    /// Profile the function calls, and write the profile as folded stacks (for flamegraph tools) in the given file
+    #[clap(long, value_parser, global = true, value_name = "FILE")]
+    profile_output: Option<String>,
+
+    /// Log each statement and function call and return of the execution on the standard error
+    #[clap(long, value_parser, global = true)]
+    trace: bool,
+
+    /// Only trace the statements and calls of the function with this name (can be repeated)
+    #[clap(long, value_parser, global = true, value_name = "NAME")]
+    trace_fn: Vec<String>,
+
+    /// Only trace the statements and calls of the functions of this module (can be repeated)
+    #[clap(long, value_parser, global = true, value_name = "MODULE")]
+    trace_module: Vec<String>,
+
+    /// Write the trace to the given file instead of the standard error
+    #[clap(long, value_parser, global = true, value_name = "FILE")]
+    trace_output: Option<String>,
+
     /// Display the tokens of the file instead of running it
-    #[clap(long, value_parser)]
+    #[clap(long, value_parser, global = true)]
     tokens: bool,
 
     /// Display the list of the functions generated from the file instead of running it
-    #[clap(long, value_parser)]
+    #[clap(long, value_parser, global = true)]
     functions: bool,
 
     /// Print the AST-like structure of the program, for fun only
-    #[clap(long, value_parser)]
+    #[clap(long, value_parser, global = true)]
     ast: bool,
 
+    /// Check the program for type errors without running it
+    #[clap(long, value_parser, global = true)]
+    check: bool,
+
     /// Disable warnings
-    #[clap(long, value_parser)]
+    #[clap(long, value_parser, global = true)]
     nowarn: bool,
 
+    /// Enable a category of warnings, or disable it with 'no-<category>' (ex: -W no-semicolon).
+    /// Categories: semicolon, redefinition, uppercase
+    #[clap(short = 'W', value_parser = errors::parse_warning_flag, global = true, value_name = "CATEGORY")]
+    warnings: Vec<(errors::WarningKind, bool)>,
+
+    /// Make the build fail if a warning is reported
+    #[clap(long, value_parser, global = true)]
+    deny_warnings: bool,
+
     /// Don't import default builtins
-    #[clap(long, value_parser)]
+    #[clap(long, value_parser, global = true)]
     nodefault: bool,
 
+    /// Maximum number of errors reported when parsing, before stopping
+    #[clap(long, value_parser, global = true, default_value_t = sloth::program::DEFAULT_MAX_ERRORS)]
+    max_errors: usize,
+
+    /// Format of the errors and warnings
+    #[clap(long, value_enum, global = true, default_value = "human")]
+    error_format: errors::ErrorFormat,
+
+    /// When to color the errors and warnings
+    #[clap(long, value_enum, global = true, default_value = "auto")]
+    color: errors::ColorChoice,
+
+    /// Print the explanation of the given error code (ex: S0404) instead of running a file
+    #[clap(long, value_parser, value_name = "CODE")]
+    explain: Option<String>,
+
     /// Disabled builtin modules
-    #[clap(short, value_parser)]
+    #[clap(short, value_parser, global = true)]
     disabled_builtins: Vec<String>,
 
     /// Arguments for the Sloth program
@@ -62,14 +141,277 @@ struct Args {
 }
 
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the project described by a sloth.toml manifest
+    Run {
+        /// Path of the manifest
+        #[clap(long, value_parser, default_value = manifest::MANIFEST_NAME)]
+        manifest_path: String,
+
+        /// Arguments for the Sloth program
+        #[clap(value_parser)]
+        arguments: Vec<String>,
+    },
+
+    /// Start an interactive session (the default when no file is given)
+    Repl,
+
+    /// Format the given files in place ('-' prints the formatted standard input).
+    /// With --check, only report the files which are not formatted
+    Fmt {
+        /// Paths of the .slo files to format
+        #[clap(value_parser, required = true)]
+        files: Vec<String>,
+    },
+
+    /// Start a language server, speaking the Language Server Protocol over the standard input and output
+    Lsp,
+
+    /// Start a debug adapter, speaking the Debug Adapter Protocol over the standard input and output
+    Dap,
+
+    /// Run a .slo file in a terminal debugger, pausing before its first statement
+    Debug {
+        /// Path of the .slo file to debug
+        #[clap(value_parser)]
+        file: String,
+
+        /// Arguments for the Sloth program
+        #[clap(value_parser)]
+        arguments: Vec<String>,
+    },
+}
+
+
+
+
+/// What to execute: the main file, the packages it can import and the arguments of the program
+struct Target {
+    filename: String,
+    packages: HashMap<String, PathBuf>,
+    arguments: Vec<String>,
+}
+
+
+/// Return the target described by the command line, reading the manifest if required
+fn get_target(args: &Args) -> Result<Target, errors::Error> {
+    match &args.command {
+        Some(Command::Run {manifest_path, arguments}) => {
+            let manifest = Manifest::from_file(manifest_path)?;
+            Ok(Target {
+                filename: manifest.entry_path().to_string_lossy().to_string(),
+                packages: manifest.resolve_dependencies()?,
+                arguments: arguments.clone(),
+            })
+        },
+
+        Some(Command::Debug {file, arguments}) => {
+            Ok(Target {filename: file.clone(), packages: HashMap::new(), arguments: arguments.clone()})
+        },
+
+        Some(Command::Repl | Command::Fmt {..} | Command::Lsp | Command::Dap) | None => match (&args.inline, &args.file) {
+            // without a file, the first positional argument is one of the program
+            (Some(source), _) => {
+                sources::register_in_memory(sources::INLINE_NAME, source);
+                let arguments = args.file.iter().chain(args.arguments.iter()).cloned().collect();
+                Ok(Target {filename: sources::INLINE_NAME.to_string(), packages: HashMap::new(), arguments})
+            },
+
+            (None, Some(f)) if f == "-" => {
+                let mut source = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+                    let err_msg = format!("Unable to read the standard input: {}", e);
+                    return Err(errors::Error::new(errors::ErrMsg::FileError(err_msg), None).with_code(codes::ErrCode::S0301));
+                }
+                sources::register_in_memory(sources::STDIN_NAME, &source);
+                Ok(Target {filename: sources::STDIN_NAME.to_string(), packages: HashMap::new(), arguments: args.arguments.clone()})
+            },
+
+            (None, Some(f)) => Ok(Target {filename: f.clone(), packages: HashMap::new(), arguments: args.arguments.clone()}),
+            (None, None) => {
+                let err_msg = "No file given. Give the path of a .slo file, or use 'run' to run a project".to_string();
+                Err(errors::Error::new(errors::ErrMsg::FileError(err_msg), None).with_code(codes::ErrCode::S0302))
+            }
+        }
+    }
+}
+
+
+
+
+/// Format the given files, or only check that they are formatted. Return whether every file was formatted
+/// without errors (and, when checking, was already formatted)
+fn format_files(files: &[String], check: bool) -> bool {
+    let (blue, reset) = (errors::color(errors::BLUE), errors::color(errors::RESET));
+    let mut success = true;
+
+    for file in files {
+        let filename = match file.as_str() {
+            "-" => {
+                let mut source = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+                    let err_msg = format!("Unable to read the standard input: {}", e);
+                    errors::Error::new(errors::ErrMsg::FileError(err_msg), None).with_code(codes::ErrCode::S0301).print();
+                    success = false;
+                    continue
+                }
+                sources::register_in_memory(sources::STDIN_NAME, &source);
+                sources::STDIN_NAME
+            },
+            f => f
+        };
+
+        let formatted = match formatter::format_file(filename) {
+            Ok(f) => f,
+            Err(errors) => {
+                for e in &errors {e.print()}
+                success = false;
+                continue
+            }
+        };
+
+        let original = sources::get(filename).unwrap_or_default();
+        if check {
+            if *original != formatted {
+                eprintln!("{blue}Not formatted{reset}: {}", filename);
+                success = false;
+            }
+        }
+        else if file == "-" {print!("{}", formatted)}
+        else if *original != formatted {
+            if let Err(e) = std::fs::write(file, formatted) {
+                let err_msg = format!("Unable to write {:?}: {}", file, e);
+                errors::Error::new(errors::ErrMsg::FileError(err_msg), None).with_code(codes::ErrCode::S0704).print();
+                success = false;
+                continue
+            }
+            eprintln!("{blue}Formatted{reset} {}", file);
+        }
+    }
+
+    success
+}
+
+
+
+
+/// Return the tracer described by the command line, if the execution is traced
+fn get_tracer(args: &Args) -> Result<Option<tracer::Tracer>, errors::Error> {
+    let traced = args.trace || args.trace_output.is_some() || !args.trace_fn.is_empty() || !args.trace_module.is_empty();
+    if !traced {return Ok(None)}
+
+    let output: Box<dyn std::io::Write> = match &args.trace_output {
+        Some(file) => match std::fs::File::create(file) {
+            Ok(f) => Box::new(std::io::LineWriter::new(f)),
+            Err(e) => {
+                let err_msg = format!("Unable to create {:?}: {}", file, e);
+                return Err(errors::Error::new(errors::ErrMsg::FileError(err_msg), None).with_code(codes::ErrCode::S0704))
+            }
+        },
+        None => Box::new(std::io::stderr())
+    };
+
+    Ok(Some(tracer::Tracer::new(output, args.trace_fn.clone(), args.trace_module.clone())))
+}
+
+
+/// Print the profile of the execution, or write it as folded stacks in the given file
+fn report_profile(profiler: &profiler::Profiler, output: &Option<String>) {
+    let (blue, reset) = (errors::color(errors::BLUE), errors::color(errors::RESET));
+
+    match output {
+        Some(file) => match std::fs::write(file, profiler.to_folded_stacks()) {
+            Ok(()) => eprintln!("{blue}Profile{reset} written to {}", file),
+            Err(e) => {
+                let err_msg = format!("Unable to write {:?}: {}", file, e);
+                errors::Error::new(errors::ErrMsg::FileError(err_msg), None).with_code(codes::ErrCode::S0704).print();
+            }
+        },
+        None => profiler.print_table()
+    }
+}
+
+
+
+
+/// Return whether the command line starts the REPL: with the 'repl' command, or without a file
+/// nor an option requiring one
+fn is_repl(args: &Args) -> bool {
+    match &args.command {
+        Some(Command::Repl) => true,
+        Some(_) => false,
+        None => args.file.is_none() && args.inline.is_none() && !(args.tokens || args.functions || args.ast || args.check)
+    }
+}
+
+
 
 
 fn main() {
     let args = Args::parse();
+    errors::set_error_format(args.error_format);
+    errors::set_color_choice(args.color);
+    errors::set_deny_warnings(args.deny_warnings);
+    for (kind, enabled) in &args.warnings {
+        errors::set_warning_enabled(*kind, *enabled);
+    }
+    let (blue, reset) = (errors::color(errors::BLUE), errors::color(errors::RESET));
+
+    if let Some(code) = &args.explain {
+        match codes::find_code(code) {
+            Ok(c) => println!("{}", codes::explain(c)),
+            Err(e) => errors::Error::new(errors::ErrMsg::NameError(e), None).with_code(codes::ErrCode::S0308).abort(),
+        }
+        return
+    }
+
+    if let Some(Command::Fmt {files}) = &args.command {
+        if !format_files(files, args.check) {std::process::exit(1)}
+        return
+    }
+
+    if let Some(Command::Lsp) = &args.command {
+        match lsp::run() {
+            Ok(shutdown) => std::process::exit(if shutdown {0} else {1}),
+            Err(e) => {
+                let err_msg = format!("Unable to communicate with the client: {}", e);
+                errors::Error::new(errors::ErrMsg::RustError(err_msg), None).with_code(codes::ErrCode::S0901).abort();
+            }
+        }
+    }
+
+    if let Some(Command::Dap) = &args.command {
+        match dap::run() {
+            Ok(code) => std::process::exit(code),
+            Err(e) => {
+                let err_msg = format!("Unable to communicate with the client: {}", e);
+                errors::Error::new(errors::ErrMsg::RustError(err_msg), None).with_code(codes::ErrCode::S0901).abort();
+            }
+        }
+    }
+
     let start_time = Instant::now();
     let build_time: Duration;
     let exec_time: Duration;
-    let filename = args.file;
+
+    if is_repl(&args) {
+        let program = SlothProgram::new("repl".to_string(), !args.nodefault, args.disabled_builtins.clone());
+        if let Err(e) = repl::Repl::new(program, !args.nowarn).run() {
+            let err_msg = format!("Unable to read the input: {}", e);
+            errors::Error::new(errors::ErrMsg::RustError(err_msg), None).with_code(codes::ErrCode::S0901).abort();
+        }
+        return
+    }
+
+    let Target {filename, packages, arguments} = match get_target(&args) {
+        Ok(t) => t,
+        Err(e) => {e.abort(); return}
+    };
+    let tracer = match get_tracer(&args) {
+        Ok(t) => t,
+        Err(e) => {e.abort(); return}
+    };
 
     if args.tokens {
         let tokens = match lexer::get_token_stream(&filename) {
@@ -84,11 +426,22 @@ fn main() {
 
     else {
         // build the program
-        let mut program: SlothProgram = match parser::build_program(filename.clone(), !args.nowarn, !args.nodefault, args.disabled_builtins) {
-            Err(e) => {e.abort(); return},
+        let mut program: SlothProgram = match parser::build_program(filename.clone(), !args.nowarn, !args.nodefault, args.disabled_builtins, packages, args.max_errors) {
+            Err((errors, stopped)) => {
+                for e in &errors {e.print()}
+                if stopped && args.error_format == errors::ErrorFormat::Human {
+                    eprintln!("{blue}Stopped{reset} after {} errors (see --max-errors)", errors.len());
+                }
+                std::process::exit(1)
+            },
             Ok(p) => p,
         };
 
+        if args.deny_warnings && errors::warnings_count() > 0 {
+            eprintln!("{blue}Stopped{reset}: {} warning(s) denied by --deny-warnings", errors::warnings_count());
+            std::process::exit(1)
+        }
+
         build_time = start_time.elapsed();
 
         if args.functions {program.print_functions()}
@@ -96,18 +449,51 @@ fn main() {
             println!("{:#?}", program);
         }
         else {
+            // type errors are reported before the execution
+            let errors = checker::check_program(&program);
+            if !errors.is_empty() {
+                for e in &errors {e.print()}
+                std::process::exit(1)
+            }
+            if args.check {
+                eprintln!("{blue}Checked{reset} without errors");
+                return
+            }
+
+            if let Some(Command::Debug {..}) = &args.command {
+                match debugger::Debugger::new() {
+                    Ok(d) => sloth::hooks::add_hook(Box::new(d)),
+                    Err(e) => {
+                        let err_msg = format!("Unable to read the input: {}", e);
+                        errors::Error::new(errors::ErrMsg::RustError(err_msg), None).with_code(codes::ErrCode::S0901).abort();
+                    }
+                }
+            }
+
+            if let Some(t) = tracer {sloth::hooks::add_hook(Box::new(t))}
+
+            let profiler = match args.profile || args.profile_output.is_some() {
+                true => {
+                    let p = Rc::new(RefCell::new(profiler::Profiler::new()));
+                    sloth::hooks::add_hook(Box::new(p.clone()));
+                    Some(p)
+                },
+                false => None
+            };
+
             unsafe {
-                let return_value = program.run(args.arguments);
+                let return_value = program.run(arguments);
                 exec_time = start_time.elapsed();
+                if let Some(p) = &profiler {report_profile(&p.borrow(), &args.profile_output)}
                 match return_value {
                     Err(e) => e.abort(),
                     Ok(v) => match v {
                         Value::Number(x) => {
-                            if args.code || args.time {println!()}
-                            if args.code {println!("\x1b[94mExited\x1b[0m with return code {}", x)};
+                            if args.code || args.time {eprintln!()}
+                            if args.code {eprintln!("{blue}Exited{reset} with return code {}", x)};
                             if args.time {
-                                println!("\x1b[94mBuilt\x1b[0m in {}ms", build_time.as_millis());
-                                println!("\x1b[94mExecuted\x1b[0m in {}ms", exec_time.as_millis());
+                                eprintln!("{blue}Built{reset} in {}ms", build_time.as_millis());
+                                eprintln!("{blue}Executed{reset} in {}ms", exec_time.as_millis());
                             }
                             std::process::exit(x as i32)
                         },
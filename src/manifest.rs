@@ -0,0 +1,186 @@
+//! Project manifests (`sloth.toml`) and the resolution of local package dependencies.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg};
+
+
+
+pub const MANIFEST_NAME: &str = "sloth.toml";
+const DEFAULT_ENTRY: &str = "main.slo";
+const REGISTRY_ENV_VAR: &str = "SLOTH_REGISTRY";
+
+
+
+
+/// Content of a `sloth.toml` file
+#[derive(Deserialize, Debug, Clone)]
+pub struct Manifest {
+    pub package: Package,
+
+    #[serde(default)]
+    pub dependencies: HashMap<String, Dependency>,
+
+    /// Local registry directory, used by the dependencies given by version
+    pub registry: Option<String>,
+
+    /// Directory containing the manifest. Every relative path of the manifest is relative to it
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+
+/// The `[package]` table of the manifest
+#[derive(Deserialize, Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub entry: Option<String>,
+}
+
+
+/// A dependency can be given as a version (from the local registry) or as a table
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Dependency {
+    Version(String),
+    Detailed {
+        path: Option<String>,
+        version: Option<String>,
+    }
+}
+
+
+
+
+impl Manifest {
+    /// Read and parse the given manifest file
+    pub fn from_file(filename: &str) -> Result<Manifest, Error> {
+        let filepath = Path::new(filename);
+
+        let content = match std::fs::read_to_string(filepath) {
+            Ok(s) => s,
+            Err(e) => {
+                let err_msg = format!("Unable to read the manifest {:?}: {}", filepath.as_os_str(), e);
+                return Err(Error::new(ErrMsg::FileError(err_msg), None).with_code(ErrCode::S0303))
+            }
+        };
+
+        let mut manifest: Manifest = match toml::from_str(&content) {
+            Ok(m) => m,
+            Err(e) => {
+                let err_msg = format!("Invalid manifest {:?}: {}", filepath.as_os_str(), e);
+                return Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0304))
+            }
+        };
+
+        manifest.root = match filepath.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        Ok(manifest)
+    }
+
+
+    /// Read the manifest of the package stored in the given directory
+    pub fn from_dir(dir: &Path) -> Result<Manifest, Error> {
+        Manifest::from_file(dir.join(MANIFEST_NAME).to_str().unwrap())
+    }
+
+
+    /// Return the path of the entry file of the package
+    pub fn entry_path(&self) -> PathBuf {
+        let entry = self.package.entry.clone().unwrap_or_else(|| DEFAULT_ENTRY.to_string());
+        self.root.join(entry)
+    }
+
+
+    /// Return the registry directory to use: the one given in the manifest, then the one given by the
+    /// SLOTH_REGISTRY environment variable, or None
+    fn registry_path(&self) -> Option<PathBuf> {
+        match &self.registry {
+            Some(r) => Some(self.root.join(r)),
+            None => std::env::var(REGISTRY_ENV_VAR).ok().map(PathBuf::from),
+        }
+    }
+
+
+    /// Return the directory containing the given dependency
+    fn dependency_dir(&self, name: &str, dependency: &Dependency) -> Result<PathBuf, Error> {
+        let version = match dependency {
+            Dependency::Detailed {path: Some(p), ..} => return Ok(self.root.join(p)),
+            Dependency::Detailed {path: None, version: Some(v)} => v,
+            Dependency::Version(v) => v,
+            Dependency::Detailed {path: None, version: None} => {
+                let err_msg = format!("Dependency '{}' requires a 'path' or a 'version'", name);
+                return Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0305))
+            }
+        };
+
+        match self.registry_path() {
+            Some(r) => Ok(r.join(name).join(version)),
+            None => {
+                let err_msg = format!("Dependency '{}' is given by version, but no registry is set (use the 'registry' key or the {} variable)", name, REGISTRY_ENV_VAR);
+                Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0305))
+            }
+        }
+    }
+
+
+    /// Return the entry file of every dependency of the package (including the dependencies of the dependencies),
+    /// indexed by package name
+    pub fn resolve_dependencies(&self) -> Result<HashMap<String, PathBuf>, Error> {
+        let mut packages = HashMap::new();
+        self.resolve_into(&mut packages, &mut vec![self.package.name.clone()])?;
+        Ok(packages)
+    }
+
+
+    fn resolve_into(&self, packages: &mut HashMap<String, PathBuf>, stack: &mut Vec<String>) -> Result<(), Error> {
+        for (name, dependency) in &self.dependencies {
+            if stack.contains(name) {
+                let err_msg = format!("Circular dependency: {} -> {}", stack.join(" -> "), name);
+                return Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0306))
+            }
+
+            let dir = self.dependency_dir(name, dependency)?;
+            let manifest = Manifest::from_dir(&dir)?;
+
+            if manifest.package.name != *name {
+                let err_msg = format!("Dependency '{}' points to the package '{}' ({:?})", name, manifest.package.name, dir.as_os_str());
+                return Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0307))
+            }
+
+            if let Dependency::Detailed {path: Some(_), version: Some(v)} = dependency {
+                if *v != manifest.package.version {
+                    let err_msg = format!("Dependency '{}' requires version {}, but version {} was found", name, v, manifest.package.version);
+                    return Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0307))
+                }
+            }
+
+            // the same package can be reached through different relative paths
+            let entry = manifest.entry_path();
+            let entry = entry.canonicalize().unwrap_or(entry);
+
+            match packages.get(name) {
+                Some(e) if *e != entry => {
+                    let err_msg = format!("Package '{}' is required from two different locations ({:?} and {:?})", name, e.as_os_str(), entry.as_os_str());
+                    return Err(Error::new(ErrMsg::ManifestError(err_msg), None).with_code(ErrCode::S0307))
+                },
+                Some(_) => continue,
+                None => {packages.insert(name.clone(), entry);}
+            }
+
+            stack.push(name.clone());
+            manifest.resolve_into(packages, stack)?;
+            stack.pop();
+        }
+
+        Ok(())
+    }
+}
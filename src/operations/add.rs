@@ -1,3 +1,5 @@
+use std::rc::Rc;
+
 use super::OperatorFunction;
 use crate::lexer::Operator::Add;
 use crate::sloth::types::Type::*;
@@ -23,10 +25,10 @@ pub fn get_all() -> Vec<OperatorFunction> {
 fn add_values(v1: Value, v2: Value) -> Value {
     match (v1, v2) {
         (Value::Number(x1), Value::Number(x2)) => Value::Number(x1 + x2),
-        (Value::Number(x), Value::String(s)) => Value::String(x.to_string() + &s),
+        (Value::Number(x), Value::String(s)) => Value::from(x.to_string() + &s),
         (Value::Boolean(b1), Value::Boolean(b2)) => Value::Boolean(b1 || b2),
-        (Value::String(s), Value::Number(x)) => Value::String(s + &x.to_string()),
-        (Value::String(s1), Value::String(s2)) => Value::String(s1 + &s2),
+        (Value::String(s), Value::Number(x)) => Value::from(Rc::unwrap_or_clone(s) + &x.to_string()),
+        (Value::String(s1), Value::String(s2)) => Value::from(Rc::unwrap_or_clone(s1) + &s2),
         _ => unreachable!()
     }
 }
\ No newline at end of file
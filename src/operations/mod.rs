@@ -42,6 +42,7 @@ impl SlothFunction for OperatorFunction {
     fn get_module(&self) -> Option<String> {None}
     fn get_name(&self) -> String {self.signature.name.clone()}
     fn get_output_type(&self) -> Type {self.signature.output_type.as_ref().unwrap().clone()}
+    fn is_public(&self) -> bool {true}
     fn get_input_types(&self) -> Option<Vec<Type>> {
         match &self.signature.input_types {
             None => None,
@@ -55,6 +56,9 @@ impl SlothFunction for OperatorFunction {
         }
     }
 
+    // the operations are given copies of the values
+    fn copies_inputs(&self) -> bool {false}
+
     unsafe fn call(&self, scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error> {
         // evaluate given values in the scope
         let values = scope.borrow().get_inputs();
@@ -0,0 +1,45 @@
+use crate::lexer::{Token, TokenStream, Separator};
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg, WarningKind};
+
+
+
+/// Name of the attribute silencing categories of warnings in the following definition
+pub const ALLOW_ATTRIBUTE: &str = "@allow";
+
+
+
+/// Return whether the stream is on the start of an attribute: '@allow('
+pub fn is_attribute_start(stream: &mut TokenStream) -> bool {
+    match (stream.current(), stream.peek(1)) {
+        (Some((Token::Identifier(n), _)), Some((Token::Separator(Separator::OpenParenthesis), _))) => n == ALLOW_ATTRIBUTE,
+        _ => false
+    }
+}
+
+
+/// Parse the attributes written before a definition, like '@allow(semicolon redefinition)',
+/// returning the categories of warnings they allow
+pub fn parse_attributes(stream: &mut TokenStream) -> Result<Vec<WarningKind>, Error> {
+    let mut allowed = Vec::new();
+
+    while is_attribute_start(stream) {
+        stream.skip(2);
+
+        loop {
+            match stream.current() {
+                Some((Token::Separator(Separator::CloseParenthesis), _)) => {stream.next(); break},
+                Some((Token::Identifier(n), p)) => {
+                    match WarningKind::from_name(&n) {
+                        Ok(k) => allowed.push(k),
+                        Err(e) => return Err(Error::new(ErrMsg::SyntaxError(e), Some(p)).with_code(ErrCode::S0104))
+                    }
+                    stream.next();
+                },
+                o => return Err(super::wrong_token(o, "warning category or ')'"))
+            }
+        }
+    }
+
+    Ok(allowed)
+}
@@ -1,6 +1,7 @@
 use crate::builtins::BuiltInImport;
 use crate::lexer::{Token, TokenStream, Separator, Keyword};
 use crate::sloth::program::SlothProgram;
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
 
 
@@ -42,6 +43,6 @@ pub fn parse_builtin(stream: &mut TokenStream, p: &mut SlothProgram, warning: bo
 
     match import.is_valid(&p.disabled_builtins) {
         Ok(..) => Ok(import),
-        Err(e) => Err(Error::new(ErrMsg::ImportError(e), Some(pos)))
+        Err(e) => Err(Error::new(ErrMsg::ImportError(e), Some(pos)).with_code(ErrCode::S0205))
     }
 }
\ No newline at end of file
@@ -74,7 +74,7 @@ fn parse_expr_suffix(stream: &mut TokenStream, program: &mut SlothProgram, warni
         Some((Token::Identifier(n), p)) => (n, p),
         Some((t, p)) => {
             let err_msg = format!("Expected identifier, got unexpected token '{}'", t.original_string());
-            return Err(Error::new(ErrMsg::SyntaxError(err_msg), Some(p)));
+            return Err(Error::new(ErrMsg::SyntaxError(err_msg), Some(p)).with_code(ErrCode::S0102));
         },
         None => return Err(eof_error(line!()))
     };
@@ -87,9 +87,9 @@ fn parse_expr_suffix(stream: &mut TokenStream, program: &mut SlothProgram, warni
         Some((Token::Separator(Separator::OpenParenthesis), _)) | Some((Token::Separator(Separator::Colon), _)) => {
             let function = parse_functioncall(stream, program, warning)?;
             // Transforms the FunctionCall expression given by the parse_functioncall function into a MethodCall
-            if let Expression::FunctionCall(_, signature, input_exprs, pos) = function {
+            if let Expression::FunctionCall(_, signature, input_exprs, pos, cache) = function {
                 let expr_pos = first_expr.1.until(pos);
-                let method_call = Expression::FunctionCall(Some(first_expr.0), signature, input_exprs, expr_pos.clone());
+                let method_call = Expression::FunctionCall(Some(first_expr.0), signature, input_exprs, expr_pos.clone(), cache);
                 (program.push_expr(method_call), expr_pos)
             }
             else {panic!("Function 'parse_functioncall' did not return an Expression::Functioncall value")}
@@ -125,7 +125,7 @@ fn parse_expr_suffix(stream: &mut TokenStream, program: &mut SlothProgram, warni
             if !is_parenthesied {Ok(expr)}
             else {
                 let err_msg = format!("Expected ')', got unexpected token '{}'", t.original_string());
-                return Err(Error::new(ErrMsg::SyntaxError(err_msg), Some(p)));
+                return Err(Error::new(ErrMsg::SyntaxError(err_msg), Some(p)).with_code(ErrCode::S0102));
             }
         },
         None => Err(eof_error(line!())),
@@ -6,7 +6,7 @@ use crate::errors::Error;
 use crate::sloth::statement::Statement;
 
 use super::expression::parse_expression;
-use super::statement::parse_statement;
+use super::statement::parse_statements;
 
 
 
@@ -17,10 +17,7 @@ pub fn parse_if(stream: &mut TokenStream, program: &mut SlothProgram, warning: b
     super::expect_token(stream, Token::Separator(Separator::OpenBracket))?;
 
     // parse the succession of statements until a closed bracket is reached
-    let mut statements: Vec<Statement> = Vec::new();
-    while !super::current_equal(stream, Token::Separator(Separator::CloseBracket))? {
-        statements.push(parse_statement(stream, program, warning)?)
-    }
+    let statements = parse_statements(stream, program, warning)?;
 
     let (_, last_pos) = super::expect_token(stream, Token::Separator(Separator::CloseBracket))?;
     Ok(Statement::If(Rc::new(condition), statements, first_pos.until(last_pos)))
@@ -34,10 +31,7 @@ pub fn parse_while(stream: &mut TokenStream, program: &mut SlothProgram, warning
     super::expect_token(stream, Token::Separator(Separator::OpenBracket))?;
 
     // parse the succession of statements until a closed bracket is reached
-    let mut statements: Vec<Statement> = Vec::new();
-    while !super::current_equal(stream, Token::Separator(Separator::CloseBracket))? {
-        statements.push(parse_statement(stream, program, warning)?)
-    }
+    let statements = parse_statements(stream, program, warning)?;
 
     let (_, last_pos) = super::expect_token(stream, Token::Separator(Separator::CloseBracket))?;
     Ok(Statement::While(Rc::new(condition), statements, first_pos.until(last_pos)))
@@ -1,12 +1,12 @@
 use crate::lexer::{Token, TokenStream, Keyword, Separator};
+use crate::sloth::bytecode::Chunk;
 use crate::sloth::function::{CustomFunction, FunctionSignature};
 use crate::sloth::program::SlothProgram;
 use crate::errors::Error;
-use crate::sloth::statement::Statement;
 use crate::sloth::types::Type;
 
 use super::types::parse_type;
-use super::statement::parse_statement;
+use super::statement::parse_statements;
 
 
 
@@ -15,7 +15,7 @@ use super::statement::parse_statement;
 
 
 
-pub fn parse_function(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, warning: bool) -> Result<CustomFunction, Error> {
+pub fn parse_function(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, marked_pub: bool, warning: bool) -> Result<CustomFunction, Error> {
     // "define' keyword
     super::expect_token(stream, Token::Keyword(Keyword::Define))?;
 
@@ -62,17 +62,16 @@ pub fn parse_function(stream: &mut TokenStream, program: &mut SlothProgram, modu
     super::expect_token(stream, Token::Separator(Separator::OpenBracket))?;
 
     // each statement until '}'
-    let mut statements: Vec<Statement> = Vec::new();
-    while !super::current_equal(stream, Token::Separator(Separator::CloseBracket))? {
-        statements.push(parse_statement(stream, program, warning)?);
-    };
+    let statements = parse_statements(stream, program, warning)?;
 
     // '}'
     super::expect_token(stream, Token::Separator(Separator::CloseBracket))?;
 
 
     // return the function
+    let inputs = input_types.len();
     let func = CustomFunction {
+        public: super::is_public(&func_name, module_name, marked_pub),
         signature: FunctionSignature::new(
             module_name.clone(),
             func_name,
@@ -81,6 +80,7 @@ pub fn parse_function(stream: &mut TokenStream, program: &mut SlothProgram, modu
             Some(output_type)
         ),
 
+        code: Chunk::compile(&statements, inputs),
         instructions: statements
     };
     Ok(func)
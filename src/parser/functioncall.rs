@@ -47,5 +47,5 @@ pub fn parse_functioncall(stream: &mut TokenStream, program: &mut SlothProgram,
 
     // owner_type, input_types and output_types will be completed in the expression call
     let func_sign = FunctionCallSignature::new(module_name, func_name, None, vec![], Type::Any);
-    Ok(Expression::FunctionCall(first_expr, func_sign, arg_exprs, functioncall_pos))
+    Ok(Expression::FunctionCall(first_expr, func_sign, arg_exprs, functioncall_pos, Rc::default()))
 }
\ No newline at end of file
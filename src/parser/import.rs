@@ -2,16 +2,18 @@ use regex::Regex;
 
 use crate::lexer::{Token, TokenStream, Keyword};
 use crate::sloth::program::SlothProgram;
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
 
 
-/// Parse an "import" statement, i.e the import of another .slo file. Different from the "builtin" statement which '''imports''' builtin functions and structures
+/// Parse an "import" statement, i.e the import of another .slo file. Different from the "builtin" statement which '''imports''' builtin functions and structures.
+/// The file can be given by its path (as a literal) or, for the packages declared in the manifest, by the package name (as an identifier)
 pub fn parse_import(stream: &mut TokenStream, program: &mut SlothProgram, warning: bool, _: String) -> Result<(), Error> {
     // import keyword
     let (_, first_pos) = super::expect_token(stream, Token::Keyword(Keyword::Import))?;
-    
-    // name of the file, as a literal (NOT an identifier)
-    let (filename, last_pos) = match stream.current() {
+
+    // name of the file, as a literal, or name of a package, as an identifier
+    let last_pos = match stream.current() {
         Some((Token::Literal(s), p)) => {
             let re = Regex::new(r#"^"(.*)""#).unwrap();
             let file_name = match re.captures(&s) {
@@ -19,23 +21,41 @@ pub fn parse_import(stream: &mut TokenStream, program: &mut SlothProgram, warnin
                 None => None,
             };
 
-            match file_name {
-                Some(v) => (v.as_str().to_string(), p),
+            let filename = match file_name {
+                Some(v) => v.as_str().to_string(),
                 None => {
                     let err_msg = format!("Expected filename, got '{}'", s);
-                    return Err(Error::new(ErrMsg::ImportError(err_msg), Some(p)))
+                    return Err(Error::new(ErrMsg::ImportError(err_msg), Some(p)).with_code(ErrCode::S0203))
+                }
+            };
+            stream.next();
+
+            // parse the file for the program
+            super::parse_file(filename, program, warning, false)?;
+            p
+        },
+
+        Some((Token::Identifier(n), p)) => {
+            let entry = match program.get_package(&n) {
+                Some(e) => e,
+                None => {
+                    let err_msg = format!("Unknown package '{}'. Packages must be declared in the dependencies of the manifest", n);
+                    return Err(Error::new(ErrMsg::ImportError(err_msg), Some(p)).with_code(ErrCode::S0204))
                 }
+            };
+            stream.next();
+
+            // parse the entry file of the package, as a module named after the package
+            if program.load_package(&n) {
+                super::parse_module(entry.to_string_lossy().to_string(), Some(n), program, warning)?;
             }
+            p
         },
 
-        o => return Err(super::wrong_token(o, "file name"))
+        o => return Err(super::wrong_token(o, "file or package name"))
     };
-    stream.next();
-
-    // parse the file for the program
-    super::parse_file(filename, program, warning, false)?;
 
     super::check_semicolon(stream, warning, &first_pos.until(last_pos))?;
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,29 +1,34 @@
 //! This module is responsible of parsing a [TokenStream] and converting it to the final execution tree.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::rc::Rc;
 
 use crate::position::Position;
 use crate::lexer::{Token, TokenStream, Separator, Keyword};
-use crate::errors::{Error, ErrMsg, Warning};
+use crate::codes::ErrCode;
+use crate::errors::{self, Error, ErrMsg, Warning, WarningKind};
 use crate::sloth::program::SlothProgram;
+use crate::sloth::statement::Statement;
 use crate::sloth::structure::ObjectBlueprint;
 
-mod types;
+pub mod types;
 mod structure;
 mod varcall;
 mod builtin;
-mod expression;
+pub mod expression;
 mod literal;
 mod list;
 mod operation;
 mod object_construction;
 mod functioncall;
 mod bracket;
-mod statement;
+pub mod statement;
 mod flow_control;
 mod statics;
 mod function;
 mod import;
+pub mod attribute;
 
 
 /*
@@ -37,7 +42,7 @@ Each function using the TokenStream to parse a particular token structure must b
 
 /// Return the EOF error
 fn eof_error() -> Error {
-    Error::new(ErrMsg::UnexpectedEOF("Unexpected end of file".to_string()), None)
+    Error::new(ErrMsg::UnexpectedEOF("Unexpected end of file".to_string()), None).with_code(ErrCode::S0103)
 }
 
 
@@ -59,7 +64,7 @@ pub fn wrong_token(given: Option<(Token, Position)>, expected: &str) -> Error {
                 Token::Literal(..) => "literal",
             });
             err_msg.push_str(&format!(" '{}'", t.original_string()));
-            Error::new(ErrMsg::SyntaxError(err_msg), Some(p))
+            Error::new(ErrMsg::SyntaxError(err_msg), Some(p)).with_code(ErrCode::S0102)
         },
         None => eof_error()
     }
@@ -110,12 +115,13 @@ pub fn check_semicolon(stream: &mut TokenStream, warn: bool, statement_pos: &Pos
                     if warn {
                         let position = Position {
                             filename: statement_pos.filename.clone(),
-                            line: statement_pos.line,
+                            line: statement_pos.last_line,
+                            last_line: statement_pos.last_line,
                             first_column: statement_pos.last_column.unwrap() + 1,
                             last_column: None,
                         };
 
-                        let warning = Warning::new("Use of a semicolon here is highly recommended".to_string(), Some(position));
+                        let warning = Warning::new(WarningKind::Semicolon, "Use of a semicolon here is highly recommended".to_string(), Some(position));
                         warning.warn();
                     };
                 }
@@ -129,6 +135,47 @@ pub fn check_semicolon(stream: &mut TokenStream, warn: bool, statement_pos: &Pos
 
 
 
+/// Return whether a definition (function, structure or field) can be accessed from other modules.
+/// The definitions of the main file are public, the ones of imported modules only if marked with 'pub'.
+/// Names starting with '_' are always private
+pub fn is_public(name: &str, module_name: &Option<String>, marked_pub: bool) -> bool {
+    !name.starts_with('_') && (module_name.is_none() || marked_pub)
+}
+
+
+
+/// Skip the tokens of a statement containing a syntax error: the stream ends after its semicolon,
+/// or on the closing bracket of the block containing it
+pub fn skip_statement(stream: &mut TokenStream) {
+    let mut depth = 0;
+
+    while let Some((t, _)) = stream.current() {
+        match t {
+            Token::Separator(Separator::OpenBracket) => depth += 1,
+            Token::Separator(Separator::CloseBracket) if depth == 0 => return,
+            Token::Separator(Separator::CloseBracket) => depth -= 1,
+            Token::Separator(Separator::SemiColon) if depth == 0 => {stream.next(); return},
+            _ => ()
+        }
+        stream.next();
+    }
+}
+
+
+
+/// Skip the tokens of a definition containing an error: the stream ends on the keyword or attribute starting the next definition
+pub fn skip_definition(stream: &mut TokenStream) {
+    while let Some((t, _)) = stream.current() {
+        match t {
+            Token::Keyword(Keyword::Builtin | Keyword::Import | Keyword::Static | Keyword::Pub | Keyword::Structure | Keyword::Define) => return,
+            _ if attribute::is_attribute_start(stream) => return,
+            _ => {stream.next();}
+        }
+    }
+}
+
+
+
 /// Check if the user defines a module (module:)
 pub fn module_check(stream: &mut TokenStream) -> Result<Option<(String, Position)>, Error> {
     if let Some((Token::Separator(Separator::Colon), _)) = stream.peek(1) {
@@ -162,86 +209,234 @@ pub fn module_check(stream: &mut TokenStream) -> Result<Option<(String, Position
 
 /// Parse a whole file, populating the program object
 pub fn parse_file(filename: String, program: &mut SlothProgram, warning: bool, is_main: bool) -> Result<(), Error> {
-    let mut stream = crate::lexer::get_token_stream(&filename)?;
-
     let module_name = match is_main {
         true => None,
         false => Some(PathBuf::from(&filename).file_stem().unwrap().to_str().unwrap().to_string()),
     };
 
-    // main building loop, going over each tokens
-    loop {
-        let token = stream.current();
-
-        match token {
-            None => break,
-
-            Some((Token::Keyword(n), p)) => {
-                match n {
-                    Keyword::Builtin => {
-                        let import = builtin::parse_builtin(&mut stream, program, warning)?;
-                        program.add_import(import);
-                    },
-                    Keyword::Import => {
-                        import::parse_import(&mut stream, program, warning, filename.clone())?;
-                    },
-                    Keyword::Static => {
-                        statics::parse_static_expr(&mut stream, program, warning)?;
-                    },
-                    Keyword::Structure => {
-                        let structure = structure::parse_structure(&mut stream, program, &module_name, warning)?;
-                        let res = program.push_struct(
-                            structure.get_signature().name,
-                            structure.get_signature().module,
-                            Box::new(structure)
-                        );
-
-                        // raise warning if the struct is overwritten
-                        match res {
-                            None => (),
-                            Some(w) => Warning::new(w, None).warn()
-                        }
-                    },
-                    Keyword::Define => {
-                        let function = function::parse_function(&mut stream, program, &module_name, warning)?;
-                        let res = program.push_function(Box::new(function));
-
-                        // raise warning if the function is overwritten
-                        match res {
-                            None => (),
-                            Some(w) => Warning::new(w, None).warn()
-                        }
-                    },
-
-                    t => {
-                        let error_msg = format!("Expected 'builtin', 'import', 'static', 'structure' or 'define', got unexpected keyword '{}'", t.to_string());
-                        return Err(Error::new(ErrMsg::SyntaxError(error_msg), Some(p)));
-                    }
-                }
-            },
+    parse_module(filename, module_name, program, warning)
+}
+
+
+
+
+/// Parse a whole file as the given module (None for the main file), populating the program object.
+/// The errors found in the definitions are stored in the program, the parsing resuming at the next definition
+pub fn parse_module(filename: String, module_name: Option<String>, program: &mut SlothProgram, warning: bool) -> Result<(), Error> {
+    let mut stream = crate::lexer::get_token_stream(&filename)?;
+
+    // the attributes of the definition importing this module don't apply to it
+    let importer_allowed = errors::set_allowed_warnings(&[]);
+
+    // main building loop, going over each definition
+    while stream.current().is_some() && !program.too_many_parse_errors() {
+        let start = stream.index();
 
-            o => return Err(wrong_token(o, "keyword"))
+        if let Err(e) = parse_definition(&mut stream, program, &module_name, &filename, warning) {
+            program.push_parse_error(e);
+
+            // the token starting the definition is skipped even if the error is on it, so the parsing moves forward
+            if stream.index() == start {stream.next();}
+            skip_definition(&mut stream);
         }
     };
+
+    errors::set_allowed_warnings(&importer_allowed);
     Ok(())
 }
 
 
 
 
+/// Parse the top-level definition starting at the current token, with its attributes.
+/// The warnings allowed by the attributes are silenced while the definition is parsed
+fn parse_definition(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, filename: &str, warning: bool) -> Result<(), Error> {
+    let allowed = attribute::parse_attributes(stream)?;
+
+    let previous = errors::set_allowed_warnings(&allowed);
+    let res = parse_keyword_definition(stream, program, module_name, filename, warning);
+    errors::set_allowed_warnings(&previous);
+
+    res
+}
+
+
+
+
+/// Parse the top-level definition (import, static, structure, function, etc.) starting at the current keyword
+fn parse_keyword_definition(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, filename: &str, warning: bool) -> Result<(), Error> {
+    match stream.current() {
+        None => Err(eof_error()),
+
+        Some((Token::Keyword(n), p)) => {
+            // 'pub' makes the following definition visible from other modules
+            let (n, p, marked_pub) = match n {
+                Keyword::Pub => match stream.next() {
+                    Some((Token::Keyword(k @ (Keyword::Define | Keyword::Structure)), p)) => (k, p, true),
+                    o => return Err(wrong_token(o, "'define' or 'structure'"))
+                },
+                k => (k, p, false)
+            };
+
+            match n {
+                Keyword::Builtin => {
+                    let import = builtin::parse_builtin(stream, program, warning)?;
+                    program.add_import(import);
+                },
+                Keyword::Import => {
+                    import::parse_import(stream, program, warning, filename.to_string())?;
+                },
+                Keyword::Static => {
+                    statics::parse_static_expr(stream, program, warning)?;
+                },
+                Keyword::Structure => {
+                    let structure = structure::parse_structure(stream, program, module_name, marked_pub, warning)?;
+                    let res = program.push_struct(
+                        structure.get_signature().name,
+                        structure.get_signature().module,
+                        Box::new(structure)
+                    );
+
+                    // raise warning if the struct is overwritten
+                    match res {
+                        None => (),
+                        Some(w) => Warning::new(WarningKind::Redefinition, w, Some(p)).warn()
+                    }
+                },
+                Keyword::Define => {
+                    let function = function::parse_function(stream, program, module_name, marked_pub, warning)?;
+                    let res = program.push_function(Box::new(function));
+
+                    // raise warning if the function is overwritten
+                    match res {
+                        None => (),
+                        Some(w) => Warning::new(WarningKind::Redefinition, w, Some(p)).warn()
+                    }
+                },
+
+                t => {
+                    let error_msg = format!("Expected 'builtin', 'import', 'static', 'pub', 'structure' or 'define', got unexpected keyword '{}'", t.to_string());
+                    return Err(Error::new(ErrMsg::SyntaxError(error_msg), Some(p)).with_code(ErrCode::S0102));
+                }
+            }
+            Ok(())
+        },
+
+        o => Err(wrong_token(o, "keyword"))
+    }
+}
+
+
+
+
 
 
 
 
-/// 
-pub fn build_program(filename: String, warning: bool, import_default_builtins: bool, disabled_builtins: Vec<String>) -> Result<SlothProgram, Error> {
+/// Build the program from its main file. The given packages can be imported by name.
+/// Up to max_errors errors found while parsing are returned together, with whether the parsing stopped because
+/// this maximum was reached
+pub fn build_program(filename: String, warning: bool, import_default_builtins: bool, disabled_builtins: Vec<String>, packages: HashMap<String, PathBuf>, max_errors: usize) -> Result<SlothProgram, (Vec<Error>, bool)> {
     let mut program = SlothProgram::new(PathBuf::from(&filename).file_stem().unwrap().to_str().unwrap().to_string(), import_default_builtins, disabled_builtins);
-    parse_file(filename, &mut program, warning, true)?;
+    program.set_max_errors(max_errors);
+    for (name, entry) in packages {
+        program.register_package(name, entry);
+    }
+
+    if let Err(e) = parse_file(filename, &mut program, warning, true) {
+        program.push_parse_error(e);
+    }
+
+    let stopped = program.too_many_parse_errors();
+    let errors = program.take_parse_errors();
+    if !errors.is_empty() {return Err((errors, stopped))}
 
     match program.import_builtins() {
         Ok(()) => (),
-        Err(e) => return Err(Error::new(ErrMsg::ImportError(e), None))
+        Err(e) => return Err((vec![Error::new(ErrMsg::ImportError(e), None).with_code(ErrCode::S0205)], false))
     };
 
+    // the structures are all known now
+    program.resolve_types();
+
     Ok(program)
+}
+
+
+
+/// Return whether the stream is on the start of a top-level definition (or of its attributes)
+fn is_definition_start(stream: &mut TokenStream) -> bool {
+    match stream.current() {
+        Some((Token::Keyword(Keyword::Builtin | Keyword::Import | Keyword::Static | Keyword::Pub | Keyword::Structure | Keyword::Define), _)) => true,
+        _ => attribute::is_attribute_start(stream)
+    }
+}
+
+
+/// Parse a chunk of source which is not a file (like an input of the REPL), as part of the main module.
+/// Its definitions are added to the program and its statements are returned, to be executed by the caller.
+/// The semicolons at the end of the statements are optional.
+/// Without allow_definitions (while the program is running), the definitions are refused
+pub fn parse_chunk(name: &str, source: &str, program: &mut SlothProgram, warning: bool, allow_definitions: bool) -> Result<Vec<Statement>, Vec<Error>> {
+    let mut stream = crate::lexer::get_token_stream_from_string(name, source).map_err(|e| vec![e])?;
+    let mut statements = Vec::new();
+    let mut has_definitions = false;
+
+    while stream.current().is_some() && !program.too_many_parse_errors() {
+        let start = stream.index();
+
+        if is_definition_start(&mut stream) && !allow_definitions {
+            let err_msg = "Definitions are not allowed here, only statements and expressions".to_string();
+            program.push_parse_error(Error::new(ErrMsg::SyntaxError(err_msg), stream.current().map(|(_, p)| p)).with_code(ErrCode::S0102));
+            stream.next();
+            skip_definition(&mut stream);
+        }
+        else if is_definition_start(&mut stream) {
+            has_definitions = true;
+            if let Err(e) = parse_definition(&mut stream, program, &None, name, warning) {
+                program.push_parse_error(e);
+                if stream.index() == start {stream.next();}
+                skip_definition(&mut stream);
+            }
+        }
+        else {
+            // besides the statements, any expression can be evaluated (ex: '+ 1 2', '[1 2]')
+            let res = match stream.current() {
+                Some((Token::Identifier(_) | Token::Literal(_) | Token::Keyword(Keyword::If | Keyword::While), _)) => {
+                    statement::parse_unterminated_statement(&mut stream, program, warning)
+                },
+                _ => expression::parse_expression(&mut stream, program, warning, None).map(|e| {
+                    let pos = e.get_pos();
+                    Statement::ExpressionCall(Rc::new(e), pos)
+                })
+            };
+
+            match res {
+                Ok(s) => {
+                    // the last statement of the chunk doesn't need a semicolon
+                    if stream.current().is_some() {
+                        if let Err(e) = check_semicolon(&mut stream, false, &s.get_pos()) {program.push_parse_error(e)}
+                    }
+                    statements.push(s)
+                },
+                Err(e) => {
+                    program.push_parse_error(e);
+                    if stream.index() == start {stream.next();}
+                    skip_statement(&mut stream);
+                }
+            }
+        }
+    }
+
+    let errors = program.take_parse_errors();
+    if !errors.is_empty() {return Err(errors)}
+
+    // the new definitions may import builtins and use new structures
+    if !has_definitions {return Ok(statements)}
+    if let Err(e) = program.import_builtins() {
+        return Err(vec![Error::new(ErrMsg::ImportError(e), None).with_code(ErrCode::S0205)])
+    }
+    program.resolve_types();
+
+    Ok(statements)
 }
\ No newline at end of file
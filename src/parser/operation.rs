@@ -54,10 +54,10 @@ pub fn parse_operation(stream: &mut TokenStream, program: &mut SlothProgram, war
             let rhs = Rc::new(parse_expression(stream, program, warning, None)?);
             let rhs_pos = rhs.get_pos();
             
-            Expression::FunctionCall(None, sign, vec![lhs, rhs], first_pos.until(rhs_pos))
+            Expression::FunctionCall(None, sign, vec![lhs, rhs], first_pos.until(rhs_pos), Rc::default())
         }
         else {
-            Expression::FunctionCall(None, sign, vec![lhs], first_pos.until(lhs_pos))
+            Expression::FunctionCall(None, sign, vec![lhs], first_pos.until(lhs_pos), Rc::default())
         }
     };
 
@@ -1,6 +1,6 @@
 use std::rc::Rc;
 
-use crate::lexer::{Token, TokenStream, Keyword};
+use crate::lexer::{Token, TokenStream, Keyword, Separator};
 use crate::sloth::program::SlothProgram;
 use crate::errors::Error;
 use crate::sloth::statement::Statement;
@@ -12,6 +12,14 @@ use super::flow_control::parse_while;
 
 
 pub fn parse_statement(stream: &mut TokenStream, program: &mut SlothProgram, warning: bool) -> Result<Statement, Error> {
+    let statement = parse_unterminated_statement(stream, program, warning)?;
+    super::check_semicolon(stream, warning, &statement.get_pos())?;
+    Ok(statement)
+}
+
+
+/// Parse a statement without the semicolon that should follow it
+pub fn parse_unterminated_statement(stream: &mut TokenStream, program: &mut SlothProgram, warning: bool) -> Result<Statement, Error> {
     let statement = match stream.current() {
         // expr call or assignment
         Some((Token::Identifier(_), _)) | Some((Token::Literal(_), _)) => {
@@ -19,7 +27,7 @@ pub fn parse_statement(stream: &mut TokenStream, program: &mut SlothProgram, war
             let target_pos = target.get_pos();
 
             // if a '=' follows, it's an assignment, else it's an expression call
-            if super::current_equal(stream, Token::Keyword(Keyword::Equal))? {
+            if let Some((Token::Keyword(Keyword::Equal), _)) = stream.current() {
                 stream.next();
                 let source = parse_expression(stream, program, warning, None)?;
                 let source_pos = source.get_pos();
@@ -37,6 +45,29 @@ pub fn parse_statement(stream: &mut TokenStream, program: &mut SlothProgram, war
         o => return Err(super::wrong_token(o, "statement"))
     };
 
-    super::check_semicolon(stream, warning, &statement.get_pos())?;
     Ok(statement)
-}
\ No newline at end of file
+}
+
+
+
+
+/// Parse the statements of a block until its closing bracket (which is not consumed).
+/// A statement with a syntax error is stored in the program and skipped, so the following ones are still parsed
+pub fn parse_statements(stream: &mut TokenStream, program: &mut SlothProgram, warning: bool) -> Result<Vec<Statement>, Error> {
+    let mut statements: Vec<Statement> = Vec::new();
+
+    while !super::current_equal(stream, Token::Separator(Separator::CloseBracket))? {
+        match parse_statement(stream, program, warning) {
+            Ok(s) => statements.push(s),
+
+            // nothing to recover at the end of the file, or once too many errors are found
+            Err(e) if stream.current().is_none() || program.too_many_parse_errors() => return Err(e),
+            Err(e) => {
+                program.push_parse_error(e);
+                super::skip_statement(stream);
+            }
+        }
+    }
+
+    Ok(statements)
+}
@@ -2,7 +2,8 @@ use std::rc::Rc;
 
 use crate::lexer::{Token, TokenStream, Keyword};
 use crate::sloth::program::SlothProgram;
-use crate::errors::{Error, Warning, ErrMsg};
+use crate::codes::ErrCode;
+use crate::errors::{Error, Warning, WarningKind, ErrMsg};
 
 use super::expression::parse_expression;
 
@@ -19,7 +20,7 @@ pub fn parse_static_expr(stream: &mut TokenStream, program: &mut SlothProgram, w
             // raise a warning if the identifier is not in full caps
             if warning && n.to_uppercase() != n {
                 let warn_msg = format!("It is recommended to set in full uppercase the name of static expressions ('{}')", n.to_uppercase());
-                Warning::new(warn_msg, Some(p.clone())).warn()
+                Warning::new(WarningKind::Uppercase, warn_msg, Some(p.clone())).warn()
             };
             stream.next();
             (n, p)
@@ -40,6 +41,6 @@ pub fn parse_static_expr(stream: &mut TokenStream, program: &mut SlothProgram, w
     // add the expression to the program's statics
     match program.push_static(&name, Rc::new(expr)) {
         Ok(_) => Ok(()),
-        Err(e) => Err(Error::new(ErrMsg::RuntimeError(e), Some(full_pos))),
+        Err(e) => Err(Error::new(ErrMsg::RuntimeError(e), Some(full_pos)).with_code(ErrCode::S0202)),
     }
 }
\ No newline at end of file
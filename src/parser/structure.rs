@@ -1,6 +1,7 @@
-use crate::lexer::{Token, TokenStream, Separator};
+use crate::lexer::{Token, TokenStream, Separator, Keyword};
 use crate::sloth::program::SlothProgram;
 use crate::sloth::structure::{CustomDefinition, StructSignature};
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
 
 use super::types::parse_type;
@@ -8,7 +9,7 @@ use super::types::parse_type;
 
 
 /// Parse a structure definition (starting with keyword "structure")
-pub fn parse_structure(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, warning: bool) -> Result<CustomDefinition, Error> {
+pub fn parse_structure(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, marked_pub: bool, warning: bool) -> Result<CustomDefinition, Error> {
     // name of the structure
     let (struct_name, first_pos) = match stream.next() {
         Some((Token::Identifier(n), p)) => (n, p),
@@ -23,9 +24,14 @@ pub fn parse_structure(stream: &mut TokenStream, program: &mut SlothProgram, mod
 
     let mut fields_name = Vec::new();
     let mut fields_type = Vec::new();
+    let mut private_fields = Vec::new();
 
     // Next is each fields of this structure, until we met a closed bracket
     while !super::current_equal(stream, Token::Separator(Separator::CloseBracket))? {
+        // the field can be marked as public
+        let field_pub = super::current_equal(stream, Token::Keyword(Keyword::Pub))?;
+        if field_pub {stream.next();}
+
         // name of the field
         let field_name = match stream.current() {
             Some((Token::Identifier(f), _)) => {stream.next(); f},
@@ -35,7 +41,7 @@ pub fn parse_structure(stream: &mut TokenStream, program: &mut SlothProgram, mod
         // check that it doesnt exist yet
         if fields_name.contains(&field_name) {
             let err_msg = format!("The name '{}' is already used for a field of the structure '{}'", field_name, struct_name);
-            return Err(Error::new(ErrMsg::DefinitionError(err_msg), Some(first_pos)))
+            return Err(Error::new(ErrMsg::DefinitionError(err_msg), Some(first_pos)).with_code(ErrCode::S0201))
         }
 
         // colon
@@ -44,6 +50,7 @@ pub fn parse_structure(stream: &mut TokenStream, program: &mut SlothProgram, mod
         // the type of the field
         let (field_type, type_pos) = parse_type(stream, program, module_name, warning)?;
 
+        if !super::is_public(&field_name, module_name, field_pub) {private_fields.push(field_name.clone())}
         fields_name.push(field_name);
         fields_type.push(field_type);
 
@@ -57,5 +64,9 @@ pub fn parse_structure(stream: &mut TokenStream, program: &mut SlothProgram, mod
 
     let fields = fields_name.into_iter().zip(fields_type.into_iter()).collect();
 
-    Ok(CustomDefinition::new(signature, fields))
+    let mut definition = CustomDefinition::new(signature, fields);
+    definition.public = super::is_public(&struct_name, module_name, marked_pub);
+    definition.private_fields = private_fields;
+
+    Ok(definition)
 }
\ No newline at end of file
@@ -1,15 +1,39 @@
 use crate::lexer::{Token, TokenStream, Separator};
 use crate::sloth::program::SlothProgram;
 use crate::sloth::types::Type;
+use crate::sloth::structure::StructSignature;
 use crate::position::Position;
 use crate::errors::Error;
 
 
-/// Parse a type (ex: num, string, list[num], Struct, list[list[string]], etc.)
+/// Return whether the stream is on a module-qualified type (ex: geo:Point).
+/// As a type can be followed by a colon (define norm for Point: -> num), the module, the colon and the name
+/// must be written without spaces
+fn is_qualified_type(stream: &mut TokenStream) -> bool {
+    match (stream.peek(0), stream.peek(1), stream.peek(2)) {
+        (Some((Token::Identifier(_), p1)), Some((Token::Separator(Separator::Colon), p2)), Some((Token::Identifier(_), p3))) => {
+            p1.line == p2.line && p2.line == p3.line
+            && p1.last_column.map(|c| c + 1) == Some(p2.first_column)
+            && p2.first_column + 1 == p3.first_column
+        },
+        _ => false
+    }
+}
+
+
+
+
+/// Parse a type (ex: num, string, list[num], Struct, geo:Struct, list[list[string]], etc.)
 pub fn parse_type(stream: &mut TokenStream, program: &mut SlothProgram, module_name: &Option<String>, warning: bool) -> Result<(Type, Position), Error> {
     let first_pos;
     let mut last_pos;
 
+    // module of a structure type. If not given, it is resolved once the whole program is parsed
+    let type_module = match is_qualified_type(stream) {
+        true => super::module_check(stream)?,
+        false => None
+    };
+
     let first_type_name = match stream.current() {
         Some((Token::Identifier(n), p)) => {
             first_pos = p.clone();
@@ -19,6 +43,14 @@ pub fn parse_type(stream: &mut TokenStream, program: &mut SlothProgram, module_n
         o => return Err(super::wrong_token(o, "type"))
     };
 
+    if let Some((m, p)) = type_module {
+        stream.next();
+        let signature = StructSignature::new(Some(m), first_type_name);
+        let position = p.until(last_pos);
+        program.push_type_annotation(signature.clone(), module_name.clone(), position.clone());
+        return Ok((Type::Object(signature), position))
+    }
+
     let return_type = match first_type_name.as_str() {
         "any" => Type::Any,
         "num" => Type::Number,
@@ -43,7 +75,11 @@ pub fn parse_type(stream: &mut TokenStream, program: &mut SlothProgram, module_n
 
             Type::List(Box::new(list_type))
         },
-        _ => {Type::Object(first_type_name)}
+        _ => {
+            let signature = StructSignature::new(None, first_type_name);
+            program.push_type_annotation(signature.clone(), module_name.clone(), first_pos.clone());
+            Type::Object(signature)
+        }
     };
 
     stream.next();
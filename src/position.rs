@@ -2,12 +2,17 @@
 
 #[derive(Clone, Debug)]
 /// Represents the position of an element (token, expression, etc.) in a file.
-/// An element can't be on 2 line at the same time
+/// An element can span several lines (a multi-line expression or block): it starts at the first column
+/// of its first line and ends at the last column of its last line
 pub struct Position {
     pub filename: String,
+
+    // index of the first and last line of the element. Equal for a single-line element
     pub line: usize,
+    pub last_line: usize,
 
-    // column index of the first and last character of the element
+    // column index of the first character of the element (on its first line)
+    // and of its last character (on its last line)
     pub first_column: usize,
     pub last_column: Option<usize>
 }
@@ -18,7 +23,13 @@ impl std::fmt::Display for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // + 1 to every indices so it starts at 1
         let last_column = match self.last_column {Some(n) => (n + 1).to_string(), None => "?".to_string()};
-        write!(f, "({}, line {}, {}-{})", self.filename, self.line + 1, self.first_column + 1, last_column)
+
+        if self.is_multiline() {
+            write!(f, "({}, line {} column {} to line {} column {})", self.filename, self.line + 1, self.first_column + 1, self.last_line + 1, last_column)
+        }
+        else {
+            write!(f, "({}, line {}, {}-{})", self.filename, self.line + 1, self.first_column + 1, last_column)
+        }
     }
 }
 
@@ -28,23 +39,22 @@ impl std::fmt::Display for Position {
 impl Position {
     pub fn to_string(&self) -> String {format!("{}", self)}
 
+    /// Return whether the element spans several lines
+    pub fn is_multiline(&self) -> bool {
+        self.last_line != self.line
+    }
+
     /// Return a new Position starting from the start of self until the end of other.
-    /// They both needs to be on the same line
+    /// Other must not start before self
     pub fn until(&self, other: Position) -> Position {
         if self.filename != other.filename {panic!("Tried to link two tokens from different files")}
-        if self.line != other.line {
-            Position {
-                filename: self.filename.clone(),
-                line: self.line, first_column: self.first_column,
-                last_column: other.last_column
-            }
-        }
-        else {
-            Position {
-                filename: self.filename.clone(),
-                line: self.line, first_column: self.first_column,
-                last_column: other.last_column
-            }
+
+        Position {
+            filename: self.filename.clone(),
+            line: self.line,
+            last_line: other.last_line,
+            first_column: self.first_column,
+            last_column: other.last_column
         }
     }
-}
\ No newline at end of file
+}
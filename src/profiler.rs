@@ -0,0 +1,164 @@
+//! Function-level profiler. It follows the function calls with an [ExecutionHook], counting the calls of each
+//! function and the time spent in it: inclusive (with the functions it called) and exclusive (without them).
+//! The profile is printed as a table, or written as folded stacks (the input format of flamegraph tools).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::errors::{self, Error, Frame};
+use crate::operations::OperatorFunction;
+use crate::builtins::BuiltInFunction;
+use crate::sloth::function::{FunctionSignature, SlothFunction};
+use crate::sloth::hooks::ExecutionHook;
+use crate::sloth::scope::Scope;
+use crate::sloth::value::Value;
+
+
+
+/// Kinds of functions, profiled separately
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum FunctionKind {
+    User,
+    Builtin,
+    Operator,
+}
+
+impl FunctionKind {
+    /// Return the kind of the function. Operators are the builtin operations and their overloads (like '@add')
+    fn of(function: &dyn SlothFunction) -> FunctionKind {
+        let any = function.as_any_ref();
+        let name = function.get_name();
+
+        if any.is::<OperatorFunction>() {FunctionKind::Operator}
+        else if any.is::<BuiltInFunction>() {FunctionKind::Builtin}
+        else if name.starts_with('@') && name != "@main" {FunctionKind::Operator}
+        else {FunctionKind::User}
+    }
+
+    fn get_title(&self) -> &'static str {
+        match self {
+            FunctionKind::User => "User functions",
+            FunctionKind::Builtin => "Builtins",
+            FunctionKind::Operator => "Operators",
+        }
+    }
+}
+
+
+/// Calls of a function and the time spent in it
+#[derive(Default)]
+struct Stats {
+    calls: u64,
+    inclusive: Duration,
+    exclusive: Duration,
+}
+
+
+/// Function call being executed
+struct Call {
+    signature: FunctionSignature,
+    start: Instant,
+    children: Duration,     // time spent in the calls it made
+}
+
+
+
+
+#[derive(Default)]
+pub struct Profiler {
+    stats: HashMap<FunctionSignature, (FunctionKind, Stats)>,
+    calls: Vec<Call>,                       // the deepest last
+    names: Vec<String>,                     // names of the functions of the calls, for the folded stacks
+    folded: HashMap<String, Duration>,      // exclusive time by stack of function names (ex: '@main;f;g')
+}
+
+
+
+
+/// Return the name of the function with its module and signature (ex: 'geo:norm for Vector: num -> num')
+fn signature_name(signature: &FunctionSignature) -> String {
+    match &signature.module {
+        Some(m) => format!("{}:{}", m, signature.to_definition_string()),
+        None => signature.to_definition_string()
+    }
+}
+
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+
+
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+
+    /// Print the functions of each kind, sorted by exclusive time, on the standard error
+    pub fn print_table(&self) {
+        let (blue, reset) = (errors::color(errors::BLUE), errors::color(errors::RESET));
+
+        let mut entries: Vec<(&FunctionSignature, &FunctionKind, &Stats)> = self.stats.iter().map(|(s, (k, st))| (s, k, st)).collect();
+        entries.sort_by(|a, b| a.1.cmp(b.1).then(b.2.exclusive.cmp(&a.2.exclusive)));
+
+        let mut kind = None;
+        for (signature, k, stats) in entries {
+            if kind != Some(*k) {
+                eprintln!();
+                eprintln!("{blue}{}{reset}", k.get_title());
+                eprintln!("{:>10} {:>14} {:>14}  function", "calls", "inclusive ms", "exclusive ms");
+                kind = Some(*k);
+            }
+            eprintln!("{:>10} {:>14.3} {:>14.3}  {}", stats.calls, as_millis(stats.inclusive), as_millis(stats.exclusive), signature_name(signature));
+        }
+    }
+
+
+    /// Return the profile as folded stacks: one line per stack of calls, with the exclusive time
+    /// (in microseconds) spent in its deepest function
+    pub fn to_folded_stacks(&self) -> String {
+        let mut lines: Vec<String> = self.folded.iter().map(|(stack, t)| format!("{} {}", stack, t.as_micros())).collect();
+        lines.sort();
+        lines.iter().map(|l| format!("{}\n", l)).collect()
+    }
+}
+
+
+
+
+impl ExecutionHook for Rc<RefCell<Profiler>> {
+    fn enter_function(&mut self, function: &dyn SlothFunction, frame: &Frame, _scope: &Rc<RefCell<Scope>>) {
+        let mut profiler = self.borrow_mut();
+        profiler.names.push(frame.to_string());
+        profiler.calls.push(Call {signature: function.get_signature(), start: Instant::now(), children: Duration::ZERO});
+    }
+
+
+    fn exit_function(&mut self, function: &dyn SlothFunction, _frame: &Frame, _result: &Result<Rc<RefCell<Value>>, Error>) {
+        let mut profiler = self.borrow_mut();
+        let call = match profiler.calls.pop() {
+            Some(c) => c,
+            None => return
+        };
+
+        let elapsed = call.start.elapsed();
+        let exclusive = elapsed.saturating_sub(call.children);
+        if let Some(parent) = profiler.calls.last_mut() {parent.children += elapsed}
+
+        let stack = profiler.names.join(";");
+        profiler.names.pop();
+        *profiler.folded.entry(stack).or_default() += exclusive;
+
+        // the time of a recursive call is already counted in the inclusive time of the outer one
+        let recursive = profiler.calls.iter().any(|c| c.signature == call.signature);
+        let (_, stats) = profiler.stats.entry(call.signature).or_insert_with(|| (FunctionKind::of(function), Stats::default()));
+        stats.calls += 1;
+        stats.exclusive += exclusive;
+        if !recursive {stats.inclusive += elapsed}
+    }
+}
@@ -0,0 +1,173 @@
+//! Interactive read-eval-print loop. The inputs are parsed as chunks of the main module of a single program:
+//! definitions are added to it, statements are executed in a scope kept between inputs, and the value
+//! of a bare expression is printed.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+
+use crate::errors::{self, WarningKind};
+use crate::parser;
+use crate::sloth::program::SlothProgram;
+use crate::sloth::scope::Scope;
+use crate::sloth::statement::Statement;
+use crate::sloth::value::Value;
+
+
+
+const PROMPT: &str = "sloth> ";
+const CONTINUATION_PROMPT: &str = "  ...> ";
+const HISTORY_FILE: &str = ".sloth_history";
+const QUIT_COMMAND: &str = ":quit";
+
+
+
+pub struct Repl {
+    program: SlothProgram,
+    scope: Rc<RefCell<Scope>>,
+    warning: bool,
+    inputs_count: usize,        // used to name the inputs in the positions of the errors
+}
+
+
+
+/// Return the path of the history file, in the home directory
+fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|h| PathBuf::from(h).join(HISTORY_FILE))
+}
+
+
+/// Return whether the input has unclosed brackets, parenthesis or strings, in which case the next line continues it
+fn is_incomplete(input: &str) -> bool {
+    let mut depth: i32 = 0;
+
+    for line in input.lines() {
+        let mut in_string = false;
+        let mut previous = ' ';
+
+        for c in line.chars() {
+            match c {
+                '"' => in_string = !in_string,
+                '/' if !in_string && previous == '/' => break,      // comment until the end of the line
+                '{' | '(' | '[' if !in_string => depth += 1,
+                '}' | ')' | ']' if !in_string => depth -= 1,
+                _ => ()
+            }
+            previous = c;
+        }
+
+        // a string can't span several lines
+        if in_string {return false}
+    }
+
+    depth > 0
+}
+
+
+/// Return the text printed for the value of an expression: strings are quoted to tell them apart
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{:?}", s),
+        v => v.to_string()
+    }
+}
+
+
+
+
+impl Repl {
+    pub fn new(program: SlothProgram, warning: bool) -> Repl {
+        // the semicolons are optional in the REPL
+        errors::set_warning_enabled(WarningKind::Semicolon, false);
+
+        Repl {
+            program,
+            scope: Rc::new(RefCell::new(Scope::new())),
+            warning,
+            inputs_count: 0,
+        }
+    }
+
+
+    /// Read and evaluate the inputs until the user quits (with ':quit' or Ctrl-D)
+    pub fn run(&mut self) -> Result<(), ReadlineError> {
+        let mut editor = Editor::<()>::new()?;
+        let history = history_path();
+        if let Some(path) = &history {
+            // there is no history on the first run
+            let _ = editor.load_history(path);
+        }
+
+        println!("Sloth {} - type '{}' or press Ctrl-D to exit", env!("CARGO_PKG_VERSION"), QUIT_COMMAND);
+
+        let mut input = String::new();
+        loop {
+            let prompt = if input.is_empty() {PROMPT} else {CONTINUATION_PROMPT};
+
+            match editor.readline(prompt) {
+                Ok(line) => {
+                    if input.is_empty() && line.trim() == QUIT_COMMAND {break}
+
+                    input.push_str(&line);
+                    input.push('\n');
+                    if is_incomplete(&input) {continue}
+
+                    if !input.trim().is_empty() {
+                        editor.add_history_entry(input.trim_end());
+                        self.eval(&input);
+                    }
+                    input.clear();
+                },
+
+                // Ctrl-C cancels the current input
+                Err(ReadlineError::Interrupted) => input.clear(),
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if let Some(path) = &history {
+            let _ = editor.save_history(path);
+        }
+        Ok(())
+    }
+
+
+    /// Parse and execute the given input, printing the errors and the value of its last statement if it is an expression
+    pub fn eval(&mut self, input: &str) {
+        self.inputs_count += 1;
+        let name = format!("<repl:{}>", self.inputs_count);
+
+        let statements = match parser::parse_chunk(&name, input, &mut self.program, self.warning, true) {
+            Ok(s) => s,
+            Err(errors) => {
+                for e in &errors {e.print()}
+                return
+            }
+        };
+
+        for (i, statement) in statements.iter().enumerate() {
+            let is_last = i + 1 == statements.len();
+
+            let res = match statement {
+                Statement::ExpressionCall(expr, _) if is_last => unsafe {
+                    expr.evaluate(self.scope.clone(), &mut self.program, false).map(|v| Some(v.borrow().to_owned()))
+                },
+                s => unsafe {s.apply(self.scope.clone(), &mut self.program).map(|_| None)}
+            };
+
+            match res {
+                Ok(Some(v)) => println!("{}: {}", value_to_string(&v), v.get_type()),
+                Ok(None) => (),
+                Err(e) => {
+                    e.print();
+                    return
+                }
+            }
+        }
+    }
+}
+
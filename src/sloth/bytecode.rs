@@ -0,0 +1,200 @@
+//! Compilation of the statements of a function into bytecode: a flat list of instructions for a stack machine
+//! (see [super::vm]), instead of a tree of expressions to walk.
+//! The variables are resolved at compilation: the local variables are accessed by their slot in the scope.
+//! Each instruction keeps the context it was compiled in: the positions of the expressions containing it,
+//! added to the errors it raises so they have the same backtrace as with the evaluation of the tree.
+
+use std::rc::Rc;
+
+use crate::position::Position;
+use super::expression::Expression;
+use super::function::{CallCache, FunctionCallSignature};
+use super::resolver;
+use super::scope::Layout;
+use super::statement::Statement;
+use super::structure::StructSignature;
+use super::value::Value;
+
+
+
+#[derive(Clone, Debug)]
+pub enum Instruction {
+    Statement,                                                  // start of a statement (at the position of its context), for the hooks
+    Literal(Value),                                             // push a new value
+    List(usize),                                                // build a list from the given number of values
+    CheckListElement(usize),                                    // check that the value has the type of the first element of its list, this number of values below
+    Local(usize, bool),                                         // push the local variable in the given slot, created if it is the target of an assignment (bool)
+    Static(String),                                             // push the static (or a variable added to the scope while running, by the debugger)
+    Field(String, bool),                                        // replace the value by its field, not shared with the copies of the value if it is modified (bool)
+    Index(bool),                                                // replace the owner (on top) and the index (below) by the element, not shared if it is modified (bool)
+    Call(FunctionCallSignature, Rc<CallCache>, usize, bool),    // call the function with the given number of arguments, and an owner (on top) if the bool is set
+    New(StructSignature, usize),                                // build an object from the given number of field values
+    Assign,                                                     // copy the source (below) into the target (on top)
+    Pop,                                                        // drop the value
+    Jump(usize),                                                // continue at the given instruction
+    JumpIfNot(usize, bool),                                     // continue at the given instruction if the value is not true. The bool is whether a non-boolean value is an error
+}
+
+
+
+
+/// The bytecode of a function
+#[derive(Clone, Debug, Default)]
+pub struct Chunk {
+    pub instructions: Vec<(Instruction, usize)>,    // instruction and the index of its context
+    pub contexts: Vec<Vec<Position>>,               // positions of the expressions (or statement) containing an instruction, the innermost first
+    pub layout: Layout,                             // variables of the scope of the function
+}
+
+
+impl Chunk {
+    /// Compile the statements of a function with the given number of inputs
+    pub fn compile(statements: &[Statement], inputs: usize) -> Chunk {
+        let chunk = Chunk {layout: resolver::resolve(inputs, statements), ..Default::default()};
+        let mut compiler = Compiler {chunk, parents: Vec::new()};
+        for statement in statements {compiler.compile_statement(statement)}
+        compiler.chunk
+    }
+}
+
+
+
+
+struct Compiler {
+    chunk: Chunk,
+    parents: Vec<usize>,    // contexts of the expressions being compiled, the innermost last
+}
+
+
+impl Compiler {
+    /// Return the index of the new context of an element at the given position, inside the current expression
+    fn push_context(&mut self, position: Position) -> usize {
+        let mut context = vec![position];
+        if let Some(parent) = self.parents.last() {
+            context.extend(self.chunk.contexts[*parent].iter().cloned());
+        }
+        self.chunk.contexts.push(context);
+        self.chunk.contexts.len() - 1
+    }
+
+
+    /// Add the instruction with the given context and return its index
+    fn emit(&mut self, instruction: Instruction, context: usize) -> usize {
+        self.chunk.instructions.push((instruction, context));
+        self.chunk.instructions.len() - 1
+    }
+
+
+    /// Set the target of the jump at the given index to the next instruction
+    fn patch_jump(&mut self, index: usize) {
+        let target = self.chunk.instructions.len();
+        match &mut self.chunk.instructions[index].0 {
+            Instruction::Jump(t) | Instruction::JumpIfNot(t, _) => *t = target,
+            _ => unreachable!()
+        }
+    }
+
+
+    fn compile_statement(&mut self, statement: &Statement) {
+        // the expressions of the statement are not inside it: their errors don't have its position
+        let parents = std::mem::take(&mut self.parents);
+        let context = self.push_context(statement.get_pos());
+
+        self.emit(Instruction::Statement, context);
+
+        match statement {
+            Statement::Assignment(target, source, _) => {
+                self.compile_expression(source, false);
+                self.compile_expression(target, true);
+                self.emit(Instruction::Assign, context);
+            },
+
+            Statement::ExpressionCall(expr, _) => {
+                self.compile_expression(expr, false);
+                self.emit(Instruction::Pop, context);
+            },
+
+            Statement::If(cond, statements, _) => {
+                self.compile_expression(cond, false);
+                let jump = self.emit(Instruction::JumpIfNot(0, true), context);
+                for s in statements {self.compile_statement(s)}
+                self.patch_jump(jump);
+            },
+
+            Statement::While(cond, statements, _) => {
+                let start = self.chunk.instructions.len();
+                self.compile_expression(cond, false);
+                let jump = self.emit(Instruction::JumpIfNot(0, false), context);
+                for s in statements {self.compile_statement(s)}
+                self.emit(Instruction::Jump(start), context);
+                self.patch_jump(jump);
+            }
+        }
+
+        self.parents = parents;
+    }
+
+
+    /// Compile the expression, leaving its value on the stack. Return the index of its context
+    fn compile_expression(&mut self, expr: &Rc<Expression>, for_assignment: bool) -> usize {
+        let context = self.push_context(expr.get_pos());
+        self.parents.push(context);
+
+        match expr.as_ref() {
+            Expression::Literal(v, _) => {self.emit(Instruction::Literal(v.clone()), context);},
+
+            Expression::ListInit(exprs, _) => {
+                for (i, e) in exprs.iter().enumerate() {
+                    let element = self.compile_expression(e, false);
+                    if i > 0 {self.emit(Instruction::CheckListElement(i), element);}
+                }
+                self.emit(Instruction::List(exprs.len()), context);
+            },
+
+            Expression::VariableAccess(None, name, _) => {
+                let instruction = match self.chunk.layout.get_slot(name) {
+                    Some(slot) => Instruction::Local(slot, for_assignment),
+                    None => Instruction::Static(name.clone())
+                };
+                self.emit(instruction, context);
+            },
+
+            // the field of an assignment target is modified, so its owner is too
+            Expression::VariableAccess(Some(owner), name, _) => {
+                self.compile_modified(owner, for_assignment);
+                self.emit(Instruction::Field(name.clone(), for_assignment), context);
+            },
+
+            // the index is evaluated before the owner
+            Expression::BracketAccess(owner, access, _) => {
+                self.compile_expression(access, false);
+                self.compile_modified(owner, for_assignment);
+                self.emit(Instruction::Index(for_assignment), context);
+            },
+
+            // the arguments are evaluated before the owner. The function can modify its owner and the inputs by reference
+            Expression::FunctionCall(owner, signature, arguments, _, cache) => {
+                for a in arguments {self.compile_modified(a, true);}
+                if let Some(o) = owner {self.compile_modified(o, true);}
+                self.emit(Instruction::Call(signature.clone(), cache.clone(), arguments.len(), owner.is_some()), context);
+            },
+
+            Expression::ObjectConstruction(signature, fields, _) => {
+                for f in fields {self.compile_expression(f, false);}
+                self.emit(Instruction::New(signature.clone(), fields.len()), context);
+            },
+
+            Expression::MainCall(_) => unreachable!("The main call is not part of a function")
+        }
+
+        self.parents.pop();
+        context
+    }
+
+
+    /// Compile an expression which can be modified. Only a field or an element is accessed differently,
+    /// a variable is not created as for an assignment
+    fn compile_modified(&mut self, expr: &Rc<Expression>, modified: bool) -> usize {
+        self.compile_expression(expr, modified && expr.is_field_access())
+    }
+}
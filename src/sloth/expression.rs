@@ -2,13 +2,15 @@ use std::cell::RefCell;
 use std::fmt::Display;
 use std::rc::Rc;
 
-use super::function::{FunctionCallSignature, SlothFunction};
+use super::function::{CallCache, FunctionCallSignature, SlothFunction};
 use super::structure::{StructSignature};
 use super::types::Type;
 use super::value::{Value, DeepClone};
-use super::scope::Scope;
+use super::scope::{Layout, Scope, RETURN_SLOT, SELF_SLOT};
+use super::hooks;
 use super::program::{SlothProgram, ENTRY_POINT_NAME};
-use crate::errors::{Error, ErrMsg};
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg, Frame};
 use crate::position::Position;
 use crate::propagate;
 
@@ -16,13 +18,13 @@ use crate::propagate;
 #[derive(Clone, Debug)]
 /// Expressions are objects that can be evaluated into a value
 pub enum Expression {
-    Literal(Value, Position),                                                                   // value of the literal
-    ListInit(Vec<Rc<Expression>>, Position),                                                    // list initialised in code. Example: [1 2 3 4 5]
-    VariableAccess(Option<Rc<Expression>>, String, Position),                                   // ExpressionID to the owner of the field and its name,
-    BracketAccess(Rc<Expression>, Rc<Expression>, Position),                                    // Owner, indexing expression
-    FunctionCall(Option<Rc<Expression>>, FunctionCallSignature, Vec<Rc<Expression>>, Position), // optional owner (for method calls), name of the function and its list of expressions to be evaluated
-    ObjectConstruction(StructSignature, Vec<Rc<Expression>>, Position),                         // The construction of an Object, with the 'new' keyword
-    MainCall(Vec<String>)                                                                       // Fake expression used to call the main function
+    Literal(Value, Position),                                                                                  // value of the literal
+    ListInit(Vec<Rc<Expression>>, Position),                                                                   // list initialised in code. Example: [1 2 3 4 5]
+    VariableAccess(Option<Rc<Expression>>, String, Position),                                                  // ExpressionID to the owner of the field and its name,
+    BracketAccess(Rc<Expression>, Rc<Expression>, Position),                                                   // Owner, indexing expression
+    FunctionCall(Option<Rc<Expression>>, FunctionCallSignature, Vec<Rc<Expression>>, Position, Rc<CallCache>), // optional owner (for method calls), name of the function, its list of expressions to be evaluated and the function last called
+    ObjectConstruction(StructSignature, Vec<Rc<Expression>>, Position),                                        // The construction of an Object, with the 'new' keyword
+    MainCall(Vec<String>)                                                                                      // Fake expression used to call the main function
 }
 
 
@@ -52,13 +54,13 @@ impl Expression {
                         if value.borrow().get_type() == list_type {values.push(value);}
                         else {
                             let err_msg = format!("Created a list of type '{}' but this value is of type '{}'", list_type, value.borrow().get_type());
-                            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), Some(expr.get_pos())));
+                            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), Some(expr.get_pos())).with_code(ErrCode::S0502));
                         }
                     }
                 }
 
 
-                Ok(Rc::new(RefCell::new(Value::List(list_type, values))))
+                Ok(Rc::new(RefCell::new(Value::List(list_type, Rc::new(values)))))
             },
 
 
@@ -71,48 +73,12 @@ impl Expression {
                     // Field of a value
                     Some(o) => {
                         // Get the reference to the owner
-                        let owner_ref = propagate!(o.evaluate(scope.clone(), program, false), p);
-                        let field = owner_ref.borrow().get_field(name);
-                        match field {
-                            Ok(v) => Ok(v),
-                            Err(e) => Err(Error::new(ErrMsg::RuntimeError(e), Some(p.clone())))
-                        }
+                        let owner_ref = propagate!(o.evaluate(scope.clone(), program, for_assignment && o.is_field_access()), p);
+                        Expression::access_field(&owner_ref, name, p, &scope, for_assignment)
                     },
 
                     // Variable in the scope
-                    None => {
-                        
-                        // if not set, create the variable or return an error depending if it's an assignment or not
-                        if !scope.borrow().is_set(name) && !program.as_ref().unwrap().is_set(name) {
-
-                            if for_assignment {
-                                match scope.try_borrow_mut() {
-                                    Ok(mut brrw) => {
-                                        match brrw.push_variable(name.clone(), Rc::new(RefCell::new(Value::Any))) {
-                                            Ok(()) => (),
-                                            Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e.to_string()), Some(p.clone())))
-                                        }
-                                    },
-                                    Err(e) => {
-                                        return Err(Error::new(ErrMsg::RustError(e.to_string()), Some(p.clone())))
-                                    }
-                                }
-                            }
-                            else {
-                                let err_msg = format!("Called uninitialized variable '{}'", name);
-                                return Err(Error::new(ErrMsg::RuntimeError(err_msg), Some(p.clone()))) 
-                            }
-                        }
-
-                        // Prevent using statics for assignment
-                        if !scope.borrow().is_set(name) && program.as_ref().unwrap().is_set(name) && for_assignment {
-                            let err_msg = format!("{} is a static expression, it cannot be assigned a value", name);
-                            return Err(Error::new(ErrMsg::RuntimeError(err_msg), Some(p.clone())))
-                        }
-
-                        // Get the value directly from the scope
-                        scope.borrow().get_variable(name.clone(), program.as_mut().unwrap())
-                    }
+                    None => Expression::access_variable(name, p, &scope, program, for_assignment)
                 }
             },
 
@@ -126,13 +92,13 @@ impl Expression {
                 // get the entry point
                 let main_function = match program.as_ref().unwrap().get_main() {
                     Ok(f) => f,
-                    Err(e) => return Err(Error::new(ErrMsg::NoEntryPoint(e), None))
+                    Err(e) => return Err(Error::new(ErrMsg::NoEntryPoint(e), None).with_code(ErrCode::S0601))
                 };
 
                 // Check that the entry point output is of type num
                 if main_function.get_output_type() != Type::Number {
                     let err_msg = format!("Your '{}' function must return a value of type '{}'", ENTRY_POINT_NAME, Type::Number);
-                    return Err(Error::new(ErrMsg::ReturnValueError(err_msg), None));
+                    return Err(Error::new(ErrMsg::ReturnValueError(err_msg), None).with_code(ErrCode::S0602));
                 }
 
 
@@ -140,14 +106,14 @@ impl Expression {
                 let values: Vec<Rc<RefCell<Value>>> = match main_function.get_input_types() {
                     None => {
                         let err_msg = format!("The '{}' function has no defined input types", ENTRY_POINT_NAME);
-                        return Err(Error::new(ErrMsg::RustError(err_msg), None));
+                        return Err(Error::new(ErrMsg::RustError(err_msg), None).with_code(ErrCode::S0901));
                     },
 
                     Some(t) => {
                         if t.len() != arguments.len() {
                             let s = if t.len() > 1 {"s"} else {""};
                             let err_msg = format!("Expected {} argument{s}, but received {}.\nNote: Expected types: {}", t.len(), arguments.len(), format_list(t));
-                            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+                            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0603));
                         }
 
                         // generate the values
@@ -158,7 +124,7 @@ impl Expression {
                                 Ok(v) => values.push(Rc::new(RefCell::new(v))),
                                 Err(e) => {
                                     let err_msg = format!("Invalid argument {}: {}", i, e);
-                                    return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None))
+                                    return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0604))
                                 }
                             }
                         };
@@ -170,44 +136,31 @@ impl Expression {
 
 
                 // The function is correct, proceed to run it
-                Expression::execute_function(main_function, None, values, program)
+                Expression::execute_function(main_function.as_ref(), None, values, None, program)
             },
 
 
 
             
-            Expression::FunctionCall(owner, signature, arguments, p) => {
+            Expression::FunctionCall(owner, signature, arguments, p, cache) => {
 
-                // Get the reference to each value. The inputs by value (without "~") are deep-cloned at a later step,
+                // Get the reference to each value. The inputs by value (without "~") are copied at a later step,
                 // and are added to the function scope even after
 
-                let inputs = arguments.iter().map(|e| e.evaluate(scope.clone(), program, false)).collect::<Result<Vec<Rc<RefCell<Value>>>, Error>>();
+                // The function can modify its owner and the inputs by reference
+                let inputs = arguments.iter().map(|e| e.evaluate(scope.clone(), program, e.is_field_access())).collect::<Result<Vec<Rc<RefCell<Value>>>, Error>>();
                 let inputs = propagate!(inputs, p);
 
                 // Get the reference to the owner value, if any
                 let owner_value = match owner {
                     Some(s) => {
-                        Some(propagate!(s.evaluate(scope.clone(), program, false), p))
+                        Some(propagate!(s.evaluate(scope.clone(), program, s.is_field_access()), p))
                     },
                     None => None
                 };
 
                 
-                // we can complete the signature with the input types and the owner type
-                let mut signature = signature.clone();
-                if let Some(v) = &owner_value {signature.owner_type = Some(v.borrow().get_type())}
-                let input_types: Vec<Type> = inputs.iter().map(|i| i.borrow().get_type()).collect();
-                signature.input_types = input_types;
-                
-                // get the function corresponding to the signature
-                let function = match program.as_ref().unwrap().get_function(&signature) {
-                    Ok(f) => f,
-                    Err(e) => {
-                        return Err(Error::new(ErrMsg::FunctionError(e), Some(p.clone())))
-                    }
-                };
-                
-                Expression::execute_function(function, owner_value, inputs, program)
+                Expression::call_function(signature, cache, owner_value, inputs, p, &scope, program)
             },
 
 
@@ -216,9 +169,9 @@ impl Expression {
 
             Expression::ObjectConstruction(signature, given_fields, p) => {
                 // Get the structure definition from the program
-                let blueprint = match program.as_mut().unwrap().get_struct(signature) {
+                let blueprint = match program.as_mut().unwrap().get_struct(signature, &scope.borrow().module) {
                     Ok(v) => v,
-                    Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), Some(p.clone())))
+                    Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), Some(p.clone())).with_code(ErrCode::S0405))
                 };
 
                 // Evaluate each given values
@@ -231,7 +184,7 @@ impl Expression {
                 // Build the object
                 let object = match blueprint.build(given_values) {
                     Ok(v) => v,
-                    Err(e) => return Err(Error::new(ErrMsg::InvalidArguments(e), Some(p.clone())))
+                    Err(e) => return Err(Error::new(ErrMsg::InvalidArguments(e), Some(p.clone())).with_code(ErrCode::S0504))
                 };
                 // Return the value
                 Ok(Rc::new(RefCell::new(Value::Object(object))))
@@ -266,7 +219,115 @@ impl Expression {
 
 
 
-    unsafe fn execute_function(function: &Box<dyn SlothFunction>, owner_value: Option<Rc<RefCell<Value>>>, arguments: Vec<Rc<RefCell<Value>>>, program: *mut SlothProgram) -> Result<Rc<RefCell<Value>>, Error> {
+    /// Return the reference to the variable (or static) with the given name. For an assignment, the variable
+    /// is created if it does not exist
+    pub unsafe fn access_variable(name: &String, p: &Position, scope: &Rc<RefCell<Scope>>, program: *mut SlothProgram, for_assignment: bool) -> Result<Rc<RefCell<Value>>, Error> {
+        // if not set, create the variable or return an error depending if it's an assignment or not
+        if !scope.borrow().is_set(name) && !program.as_ref().unwrap().is_set(name) {
+
+            if for_assignment {
+                match scope.try_borrow_mut() {
+                    Ok(mut brrw) => {
+                        match brrw.push_variable(name.clone(), Rc::new(RefCell::new(Value::Any))) {
+                            Ok(()) => (),
+                            Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e.to_string()), Some(p.clone())).with_code(ErrCode::S0901))
+                        }
+                    },
+                    Err(e) => {
+                        return Err(Error::new(ErrMsg::RustError(e.to_string()), Some(p.clone())).with_code(ErrCode::S0901))
+                    }
+                }
+            }
+            else {
+                let suggestion = scope.borrow().variable_suggestion(name, program.as_ref().unwrap());
+                let err_msg = format!("Called uninitialized variable '{}'{}", name, suggestion);
+                return Err(Error::new(ErrMsg::RuntimeError(err_msg), Some(p.clone())).with_code(ErrCode::S0401)) 
+            }
+        }
+
+        // Prevent using statics for assignment
+        if !scope.borrow().is_set(name) && program.as_ref().unwrap().is_set(name) && for_assignment {
+            let err_msg = format!("{} is a static expression, it cannot be assigned a value", name);
+            return Err(Error::new(ErrMsg::RuntimeError(err_msg), Some(p.clone())).with_code(ErrCode::S0406))
+        }
+
+        // Get the value directly from the scope
+        scope.borrow().get_variable(name.clone(), program.as_mut().unwrap())
+    }
+
+
+    /// Return the reference to the local variable in the given slot. If it is not set, the static of the same
+    /// name is used, or the variable is created for an assignment
+    pub unsafe fn access_slot(slot: usize, p: &Position, scope: &Rc<RefCell<Scope>>, program: *mut SlothProgram, for_assignment: bool) -> Result<Rc<RefCell<Value>>, Error> {
+        if let Some(v) = scope.borrow().get_slot(slot) {return Ok(v)}
+
+        let name = scope.borrow().get_layout().get_name(slot);
+        if for_assignment && !program.as_ref().unwrap().is_set(&name) {
+            let value = Rc::new(RefCell::new(Value::Any));
+            scope.borrow_mut().set_slot(slot, value.clone());
+            return Ok(value)
+        }
+
+        Expression::access_variable(&name, p, scope, program, for_assignment)
+    }
+
+
+    /// Return the reference to the field of the owner (an element for a list). If the field is modified,
+    /// it is not shared with the copies of the owner
+    pub fn access_field(owner_ref: &Rc<RefCell<Value>>, name: &String, p: &Position, scope: &Rc<RefCell<Scope>>, modified: bool) -> Result<Rc<RefCell<Value>>, Error> {
+        let module = &scope.borrow().module;
+        let field = match modified {
+            true => match owner_ref.try_borrow_mut() {
+                Ok(mut borrow) => borrow.get_field_mut(name, module),
+                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), Some(p.clone())).with_code(ErrCode::S0901))
+            },
+            false => owner_ref.borrow().get_field(name, module)
+        };
+        match field {
+            Ok(v) => Ok(v),
+            Err(e) => Err(Error::new(ErrMsg::RuntimeError(e), Some(p.clone())).with_code(ErrCode::S0407))
+        }
+    }
+
+
+    /// Call the function matching the signature, completed with the types of the owner and of the inputs
+    pub unsafe fn call_function(signature: &FunctionCallSignature, cache: &CallCache, owner_value: Option<Rc<RefCell<Value>>>, inputs: Vec<Rc<RefCell<Value>>>, p: &Position, scope: &Rc<RefCell<Scope>>, program: *mut SlothProgram) -> Result<Rc<RefCell<Value>>, Error> {
+        let owner_type = owner_value.as_ref().map(|v| v.borrow().get_type());
+        let input_types: Vec<Type> = inputs.iter().map(|i| i.borrow().get_type()).collect();
+        let caller_module = scope.borrow().module.clone();
+        let version = program.as_ref().unwrap().get_functions_version();
+
+        let function = match cache.get(version, &caller_module, &owner_type, &input_types) {
+            Some(f) => f,
+            None => {
+                // we can complete the signature with the input types and the owner type
+                let mut signature = signature.clone();
+                signature.owner_type = owner_type.clone();
+                signature.input_types = input_types.clone();
+
+                // get the function corresponding to the signature
+                let function: *const dyn SlothFunction = match program.as_ref().unwrap().get_function(&signature, &caller_module) {
+                    Ok(f) => f.as_ref(),
+                    Err(e) => {
+                        return Err(Error::new(ErrMsg::FunctionError(e), Some(p.clone())).with_code(ErrCode::S0404))
+                    }
+                };
+                cache.set(version, caller_module, owner_type, input_types, function);
+                function
+            }
+        };
+
+        Expression::execute_function(&*function, owner_value, inputs, Some(p.clone()), program)
+    }
+
+
+
+
+
+
+    /// Run the function with the given arguments, in a new scope. The errors raised in the function are given
+    /// a frame of the call stack, with the position of the call (None for the main function)
+    pub unsafe fn execute_function(function: &dyn SlothFunction, owner_value: Option<Rc<RefCell<Value>>>, arguments: Vec<Rc<RefCell<Value>>>, call_position: Option<Position>, program: *mut SlothProgram) -> Result<Rc<RefCell<Value>>, Error> {
 
         // Whether the arguments are passed by value or by reference
         let inputs_ref_or_cloned: Vec<bool> = match function.get_signature().input_types {
@@ -275,78 +336,68 @@ impl Expression {
         };
 
 
-        // Create a new scope for the execution of the function
-        let func_scope = Rc::new(RefCell::new(Scope::new()));
-
+        // Create a new scope for the execution of the function, with the @return variable (default value),
+        // the "@self" variable (the reference to the owner) and the input variables (@0, @1, etc.)
+        let mut func_scope = Scope::with_layout(function.get_module(), function.get_layout(arguments.len()));
+        func_scope.set_slot(RETURN_SLOT, Rc::new(RefCell::new(function.get_output_type().default())));
+        if let Some(v) = owner_value {func_scope.set_slot(SELF_SLOT, v)}
 
-        // Create the input variable (@0, @1, etc.) with the default value
         for (i, value) in arguments.iter().enumerate() {
             let mut v = value.clone();
 
-            // if the values are cloned, allocate a new Value instead of using the reference
+            // if the values are cloned, allocate a new Value instead of using the reference.
+            // A string or a list is only copied if the function modifies it, but an object is copied
+            // entirely, as its fields are shared by its copies
             // TODO: Is it inverted ?
-            if !inputs_ref_or_cloned[i] {
-                let cloned_value = match value.borrow().to_owned().deep_clone() {
-                    Ok(v) => v,
-                    Err(e) => return Err(Error::new(ErrMsg::InvalidArguments(e), None))
-                };
-
-                v = cloned_value;
+            if !inputs_ref_or_cloned[i] && function.copies_inputs() {
+                if value.borrow().get_type().contains_object() {
+                    v = match value.borrow().deep_clone() {
+                        Ok(v) => v,
+                        Err(e) => return Err(Error::new(ErrMsg::InvalidArguments(e), None).with_code(ErrCode::S0901))
+                    };
+                }
+                else {
+                    v = Rc::new(RefCell::new(value.borrow().to_owned()));
+                }
             }
 
-
-            match func_scope.try_borrow_mut() {
-                Ok(mut reference) => match (*reference).push_variable(format!("@{}", i), v) {
-                    Ok(()) => (),
-                    Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), None))
-                },
-                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None))
-            };
+            func_scope.set_slot(Layout::input_slot(i), v);
         }
+        let func_scope = Rc::new(RefCell::new(func_scope));
 
-        // Create the @return variable, with default value, and the "@self" variable, containing a copy of the value stored in the variable
-        {
-            let default_value = function.get_output_type().default();
-            match func_scope.try_borrow_mut() {
-                Ok(mut reference) => {
-                    match (*reference).push_variable("@return".to_string(), Rc::new(RefCell::new(default_value))) {
-                        Ok(()) => (),
-                        Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), None))
-                    };
-
-                    match owner_value {
-                        Some(v) => {
-                            match (*reference).push_variable("@self".to_string(), v.clone()) {
-                                Ok(()) => (),
-                                Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), None))
-                            };
-                        },
+        let frame = || Frame {
+            name: function.get_name(),
+            module: function.get_module(),
+            owner_type: function.get_owner_type(),
+            call_position: call_position.clone(),
+        };
 
-                        None => ()
-                    };
-                },
-                Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), None))
-            };
-        }
+        // the frame is only built for the hooks if there are some
+        let hooked_frame = match hooks::is_active() {
+            true => Some(frame()),
+            false => None
+        };
+        if let Some(f) = &hooked_frame {hooks::enter_function(function, f, &func_scope)}
 
         // run the method in the given scope
-        function.call(func_scope.clone(), program.as_mut().unwrap())?;
-
-
-
-        // return the value in the '@return' variable, but check its type first
-        let res = match func_scope.borrow().get_variable("@return".to_string(), program.as_mut().unwrap()) {
-            Ok(v) => {
-                let brrw = v.borrow();
-                if brrw.get_type() != function.get_output_type() {
-                    let err_msg = format!("Function {} should return a value of type {}, but it returned {} which is of type {}", function.get_name(), function.get_output_type(), brrw.to_string(), brrw.get_type());
-                    Err(Error::new(ErrMsg::ReturnValueError(err_msg), None))
-                }
-                else {Ok(v.clone())}
-            },
-            Err(e) => Err(e)
+        let res = match function.call(func_scope.clone(), program.as_mut().unwrap()) {
+            Err(e) => Err(e.in_frame(frame())),
+
+            // return the value in the '@return' variable, but check its type first
+            Ok(()) => match func_scope.borrow().get_slot(RETURN_SLOT) {
+                Some(v) => {
+                    let brrw = v.borrow();
+                    if brrw.get_type() != function.get_output_type() {
+                        let err_msg = format!("Function {} should return a value of type {}, but it returned {} which is of type {}", function.get_name(), function.get_output_type(), brrw.to_string(), brrw.get_type());
+                        Err(Error::new(ErrMsg::ReturnValueError(err_msg), None).with_code(ErrCode::S0505))
+                    }
+                    else {Ok(v.clone())}
+                },
+                None => unreachable!("The @return variable is set with the scope")
+            }
         };
 
+        if let Some(f) = &hooked_frame {hooks::exit_function(function, f, &res)}
         res
     }
 
@@ -357,13 +408,19 @@ impl Expression {
 
 
 
+    /// Return whether the expression is the access to a field or to an element
+    pub fn is_field_access(&self) -> bool {
+        matches!(self, Expression::VariableAccess(Some(_), _, _) | Expression::BracketAccess(..))
+    }
+
+
     /// Return the position of the expression
     pub fn get_pos(&self) -> Position {
         match self {
             Expression::Literal(_, p) => p,
             Expression::ListInit(_, p) => p,
             Expression::VariableAccess(_, _, p) => p,
-            Expression::FunctionCall(_, _, _, p) => p,
+            Expression::FunctionCall(_, _, _, p, _) => p,
             Expression::ObjectConstruction(_, _, p) => p,
             Expression::BracketAccess(_, _, p) => p,
             Expression::MainCall(_) => unreachable!()
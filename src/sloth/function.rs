@@ -2,11 +2,14 @@ use std::cell::RefCell;
 use std::fmt::Debug;
 use std::rc::Rc;
 
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
+use super::bytecode::Chunk;
 use super::program::SlothProgram;
 use super::statement::Statement;
 use super::{types::Type};
-use super::scope::{Scope};
+use super::scope::{Layout, Scope};
+use super::structure::ObjectToAny;
 
 /// Signature of a defined function; its name, module, input, output, etc.
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
@@ -22,6 +25,25 @@ impl FunctionSignature {
     pub fn new(module: Option<String>, name: String, owner_type: Option<Type>, input_types: Option<Vec<(Type, bool)>>, output_type: Option<Type>) -> FunctionSignature {
         FunctionSignature {module, name, owner_type, input_types, output_type}
     }
+
+
+    /// Return the signature as written in a definition, without the module (ex: 'norm for Vector: num -> num')
+    pub fn to_definition_string(&self) -> String {
+        let owner = match &self.owner_type {
+            Some(t) => format!(" for {}", t),
+            None => String::new()
+        };
+        let inputs = match &self.input_types {
+            Some(v) => v.iter().map(|(t, by_ref)| format!("{}{}", if *by_ref {"~"} else {""}, t)).collect::<Vec<String>>().join(" "),
+            None => "...".to_string()
+        };
+        let output = match &self.output_type {
+            Some(t) => t.to_string(),
+            None => "any".to_string()
+        };
+
+        format!("{}{}: {} -> {}", self.name, owner, inputs, output)
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Clone, Hash)]
@@ -42,9 +64,62 @@ impl FunctionCallSignature {
 
 
 
+/// Function found for the last execution of a function call, with the types it was called with.
+/// The next executions with the same types use it without looking it up, until the functions of the program change
+#[derive(Debug, Default)]
+pub struct CallCache(RefCell<Option<CachedCall>>);
+
+#[derive(Debug)]
+struct CachedCall {
+    version: u64,                           // version of the functions of the program when the function was found
+    caller_module: Option<String>,
+    owner_type: Option<Type>,
+    input_types: Vec<Type>,
+    // The function, boxed in the map of the functions of the program. Its address is stable as long as the map is not
+    // modified, and each insertion in the map or rebuild of it changes the version, so a pointer of the current version is valid
+    function: *const dyn SlothFunction,
+}
 
 
-pub trait SlothFunction: Debug {
+/// Return whether the types are the same. Unlike the comparison of types, 'any' only matches 'any', even in lists
+fn is_same_type(a: &Type, b: &Type) -> bool {
+    match (a, b) {
+        (Type::List(a), Type::List(b)) => is_same_type(a, b),
+        _ => a.strict_eq(b)
+    }
+}
+
+impl CallCache {
+    /// Return the function cached for the given call, if it was found for the same types in the same version of the functions
+    pub fn get(&self, version: u64, caller_module: &Option<String>, owner_type: &Option<Type>, input_types: &[Type]) -> Option<*const dyn SlothFunction> {
+        let cache = self.0.borrow();
+        let c = cache.as_ref()?;
+
+        let same_owner = match (&c.owner_type, owner_type) {
+            (Some(a), Some(b)) => is_same_type(a, b),
+            (a, b) => a.is_none() && b.is_none()
+        };
+        let same_inputs = c.input_types.len() == input_types.len()
+            && std::iter::zip(&c.input_types, input_types).all(|(a, b)| is_same_type(a, b));
+
+        match c.version == version && c.caller_module == *caller_module && same_owner && same_inputs {
+            true => Some(c.function),
+            false => None
+        }
+    }
+
+
+    /// Cache the function found for the call
+    pub fn set(&self, version: u64, caller_module: Option<String>, owner_type: Option<Type>, input_types: Vec<Type>, function: *const dyn SlothFunction) {
+        *self.0.borrow_mut() = Some(CachedCall {version, caller_module, owner_type, input_types, function});
+    }
+}
+
+
+
+
+
+pub trait SlothFunction: Debug + ObjectToAny {
     /// Return the type owning this function, or None if this is not a method
     fn get_owner_type(&self) -> Option<Type>;
 
@@ -63,6 +138,20 @@ pub trait SlothFunction: Debug {
     /// Return the input types of the function
     fn get_input_types(&self) -> Option<Vec<Type>>;
 
+    /// Return whether the function can be called from other modules than its own
+    fn is_public(&self) -> bool;
+
+    /// Return whether the inputs passed by value are copied for the function. It is only useless if
+    /// the function never modifies them
+    fn copies_inputs(&self) -> bool {
+        true
+    }
+
+    /// Return the variables of the scope of the function, called with the given number of inputs
+    fn get_layout(&self, inputs: usize) -> Layout {
+        Layout::new(inputs, Vec::new())
+    }
+
     /// Execute the function
     unsafe fn call(&self,  scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error>;
 }
@@ -75,6 +164,8 @@ pub trait SlothFunction: Debug {
 pub struct CustomFunction {
     pub signature: FunctionSignature,
     pub instructions: Vec<Statement>,
+    pub code: Chunk,                    // the instructions compiled, which are run
+    pub public: bool,
 }
 
 
@@ -84,6 +175,7 @@ impl SlothFunction for CustomFunction {
     fn get_module(&self) -> Option<String> {self.signature.module.clone()}
     fn get_name(&self) -> String {self.signature.name.clone()}
     fn get_output_type(&self) -> Type {self.signature.output_type.as_ref().unwrap().clone()}
+    fn is_public(&self) -> bool {self.public}
     fn get_input_types(&self) -> Option<Vec<Type>> {
         match &self.signature.input_types {
             None => None,
@@ -97,6 +189,14 @@ impl SlothFunction for CustomFunction {
         }
     }
 
+    // the layout of the compiled code, unless the number of inputs is wrong (an error when called)
+    fn get_layout(&self, inputs: usize) -> Layout {
+        match inputs == self.code.layout.inputs {
+            true => self.code.layout.clone(),
+            false => Layout::new(inputs, Vec::new())
+        }
+    }
+
     unsafe fn call(&self, scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error> {
         // get the given arguments
         let args = scope.borrow().get_inputs();
@@ -109,7 +209,7 @@ impl SlothFunction for CustomFunction {
         // Check that the number of inputs given matches the number required
         if args.len() != self_inputs.len() {
             let err_msg = format!("Called function {} with {} argument(s), but the function requires {} argument(s)", self.get_name(), args.len(), self_inputs.len());
-            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+            return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
         }
 
         // Check that the given input types match the ones from the definition
@@ -118,16 +218,12 @@ impl SlothFunction for CustomFunction {
             let given_type = given.borrow().get_type();
             if given_type != *required {
                 let err_msg = format!("Function {} was called with argument of type {} at position {}, where argument of type {} was required", self.get_name(), given_type, i, required);
-                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None));
+                return Err(Error::new(ErrMsg::InvalidArguments(err_msg), None).with_code(ErrCode::S0506));
             }
             i += 1;
         }
 
-        // Call each statement of the function
-        for statement in &self.instructions {
-            statement.apply(scope.clone(), program)?;
-        };
-
-        return Ok(())
+        // Run the compiled statements of the function
+        self.code.run(scope, program)
     }
 }
\ No newline at end of file
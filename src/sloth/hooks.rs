@@ -0,0 +1,101 @@
+//! Hooks observing the execution of a program: they are notified before each statement is applied,
+//! and around each function call. They are used by the debuggers and the profiler.
+//! A hook is taken out while it is notified, so the code it runs itself (like evaluating an expression
+//! in the paused scope) is not observed.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::errors::{Error, Frame};
+use crate::position::Position;
+use super::function::SlothFunction;
+use super::program::SlothProgram;
+use super::scope::Scope;
+use super::value::Value;
+
+
+
+/// Observer of the execution of a program
+pub trait ExecutionHook {
+    /// Called before the statement at the given position is applied in the given scope
+    fn before_statement(&mut self, _position: &Position, _scope: &Rc<RefCell<Scope>>, _program: &mut SlothProgram) {}
+
+    /// Called when a function is called, once its scope contains its arguments
+    fn enter_function(&mut self, _function: &dyn SlothFunction, _frame: &Frame, _scope: &Rc<RefCell<Scope>>) {}
+
+    /// Called when a function returns, with its return value or its error
+    fn exit_function(&mut self, _function: &dyn SlothFunction, _frame: &Frame, _result: &Result<Rc<RefCell<Value>>, Error>) {}
+}
+
+
+
+/// When a debugger pauses the execution again, once resumed
+pub enum StepMode {
+    Step,               // at the next statement
+    Next(usize),        // at a statement of a call depth lower or equal to this one
+    Out(usize),         // at a statement of a call depth strictly lower than this one
+    Continue,           // only at the breakpoints
+}
+
+impl StepMode {
+    /// Return whether the execution pauses at a statement of the given call depth
+    pub fn pauses_at(&self, depth: usize) -> bool {
+        match self {
+            StepMode::Step => true,
+            StepMode::Next(d) => depth <= *d,
+            StepMode::Out(d) => depth < *d,
+            StepMode::Continue => false
+        }
+    }
+}
+
+
+
+thread_local! {
+    static HOOKS: RefCell<Vec<Box<dyn ExecutionHook>>> = RefCell::new(Vec::new());
+
+    // checked first, so the execution is not slowed down without hooks
+    static ACTIVE: Cell<bool> = const {Cell::new(false)};
+}
+
+
+
+
+/// Add a hook notified during the execution of the programs
+pub fn add_hook(hook: Box<dyn ExecutionHook>) {
+    HOOKS.with(|h| h.borrow_mut().push(hook));
+    ACTIVE.with(|a| a.set(true));
+}
+
+
+/// Return whether a hook is observing the execution
+pub fn is_active() -> bool {
+    ACTIVE.with(|a| a.get())
+}
+
+
+/// Call the given function on each hook, taken out of the list during the call
+fn notify(f: impl FnMut(&mut Box<dyn ExecutionHook>)) {
+    let mut hooks = HOOKS.with(|h| std::mem::take(&mut *h.borrow_mut()));
+    hooks.iter_mut().for_each(f);
+    HOOKS.with(|h| {
+        let mut current = h.borrow_mut();
+        hooks.append(&mut current);
+        *current = hooks;
+    });
+}
+
+
+pub fn before_statement(position: &Position, scope: &Rc<RefCell<Scope>>, program: &mut SlothProgram) {
+    if is_active() {notify(|h| h.before_statement(position, scope, program))}
+}
+
+
+pub fn enter_function(function: &dyn SlothFunction, frame: &Frame, scope: &Rc<RefCell<Scope>>) {
+    if is_active() {notify(|h| h.enter_function(function, frame, scope))}
+}
+
+
+pub fn exit_function(function: &dyn SlothFunction, frame: &Frame, result: &Result<Rc<RefCell<Value>>, Error>) {
+    if is_active() {notify(|h| h.exit_function(function, frame, result))}
+}
@@ -7,4 +7,8 @@ pub mod function;
 pub mod scope;
 pub mod program;
 pub mod statement;
-pub mod expression;
\ No newline at end of file
+pub mod expression;
+pub mod resolver;
+pub mod bytecode;
+pub mod vm;
+pub mod hooks;
\ No newline at end of file
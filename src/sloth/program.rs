@@ -1,20 +1,28 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use crate::errors::Error;
-use super::function::{SlothFunction, FunctionSignature, FunctionCallSignature};
+use crate::position::Position;
+use super::function::{SlothFunction, FunctionSignature, FunctionCallSignature, CustomFunction};
 use super::scope::Scope;
 use super::expression::Expression;
-use super::structure::{StructSignature, ObjectBlueprint};
+use super::structure::{StructSignature, ObjectBlueprint, CustomDefinition};
 use super::types::Type;
 use super::value::Value;
 use crate::builtins;
+use crate::suggestions;
 
 
 
 pub const ENTRY_POINT_NAME: &str = "@main";
 const DEFAULT_BUILTIN_IMPORTS: [&str; 2] = ["io", "lists"];
+pub const DEFAULT_MAX_ERRORS: usize = 20;
+
+
+/// Key of the function index: module, name and owner type of a function
+type FunctionKey = (Option<String>, String, Option<Type>);
 
 
 
@@ -28,6 +36,8 @@ const DEFAULT_BUILTIN_IMPORTS: [&str; 2] = ["io", "lists"];
 pub struct SlothProgram {
     _filename: String,
     functions: HashMap<FunctionSignature, Box<dyn SlothFunction>>,
+    function_index: HashMap<FunctionKey, Vec<FunctionSignature>>,   // signatures of the functions, by module, name and owner type
+    functions_version: u64,                                     // changed each time the functions change, invalidating the call caches
     structures: HashMap<StructSignature, Box<dyn ObjectBlueprint>>,
 
     // A static is an expression defined like a global variable (ex: static NUMBER = 34;). The expression
@@ -41,7 +51,20 @@ pub struct SlothProgram {
     imported_modules: Vec<String>,
 
     // list of every builtin modules disabled
-    pub disabled_builtins: Vec<String>
+    pub disabled_builtins: Vec<String>,
+
+    // entry file of each package declared as a dependency in the manifest, by package name
+    packages: HashMap<String, PathBuf>,
+
+    // list of every package already parsed, so a package imported twice is only parsed once
+    loaded_packages: Vec<String>,
+
+    // every structure type written in the source (with the module it is written in), checked once the program is built
+    type_annotations: Vec<(StructSignature, Option<String>, Position)>,
+
+    // errors found while parsing (mostly syntax errors), the parser recovering from each of them until max_errors are found
+    parse_errors: Vec<Error>,
+    max_errors: usize,
 }
 
 impl SlothProgram {
@@ -49,13 +72,23 @@ impl SlothProgram {
         let mut program = SlothProgram {
             _filename: filename,
             functions: HashMap::new(),
+            function_index: HashMap::new(),
+            functions_version: 0,
             structures: HashMap::new(),
 
             statics: HashMap::new(),
 
             imported_modules: Vec::new(),
             builtins: Vec::new(),
-            disabled_builtins: disabled_builtins
+            disabled_builtins: disabled_builtins,
+
+            packages: HashMap::new(),
+            loaded_packages: Vec::new(),
+
+            type_annotations: Vec::new(),
+
+            parse_errors: Vec::new(),
+            max_errors: DEFAULT_MAX_ERRORS,
         };
 
         // import default operator functions
@@ -83,51 +116,149 @@ impl SlothProgram {
             if !self.imported_modules.contains(&m) {self.imported_modules.push(m)}
         }
 
-        match self.functions.insert(function.get_signature(), function) {
+        let signature = function.get_signature();
+        self.functions_version += 1;
+        match self.functions.insert(signature.clone(), function) {
             Some(f) => {
                 let msg = format!("Redefinition of function {}. Previous definition was overwritten", f.get_name());
                 Some(msg)
             }
-            None => None
+            None => {
+                self.function_index.entry(function_key(&signature)).or_default().push(signature);
+                None
+            }
         }
     }
 
 
+    /// Return the version of the functions of the program, changed each time a function is added or modified.
+    /// The functions resolved for a version stay at the same address until it changes
+    pub fn get_functions_version(&self) -> u64 {
+        self.functions_version
+    }
+
 
 
-    /// Return the requested function definition
-    pub fn get_function(&self, signature: &FunctionCallSignature) -> Result<&Box<dyn SlothFunction>, String> {
-        let mut signatures = Vec::new();
-        for (key, _) in &self.functions {
-            signatures.push(key)
-        }
+
+    /// Return the signatures of the functions with the name and module of the call, which can be called from the
+    /// caller's module. The owner and input types are not checked, as they are only known at run time
+    fn find_functions_by_name(&self, signature: &FunctionCallSignature, caller_module: &Option<String>) -> Result<Vec<&FunctionSignature>, String> {
+        // name of the function
+        let mut signatures: Vec<&FunctionSignature> = self.function_index.iter()
+            .filter(|((_, name, _), _)| *name == signature.name)
+            .flat_map(|(_, v)| v)
+            .collect();
 
         // remove each signature that don't match, criteria by criteria.
         // at each point, check if no signature is left, in order to return a
         // fitting error msg
-
-        // name of the function
-        signatures.retain(|k| k.name == *signature.name);
         if signatures.is_empty() {
-            return Err(format!("Function '{}' is not defined", signature.name))
+            return Err(format!("Function '{}' is not defined{}", signature.name, self.function_suggestion(&signature.name, &None, caller_module)))
         }
 
         // module of the function
         if let Some(module) = &signature.module {
             // return err if the module was never imported
             if !self.imported_modules.contains(&module) {
-                return Err(format!("Module '{}' was not imported", module))
+                let suggestion = suggestions::did_you_mean(module, self.imported_modules.iter().map(|m| m.as_str()));
+                return Err(format!("Module '{}' was not imported{}", module, suggestion))
             }
 
             signatures.retain(|k| k.module.is_none() || k.module == Some(module.clone()));
             if signatures.is_empty() {
-                return Err(format!("Function '{}' is not defined in the module '{}'", signature.name, module))
+                let suggestion = self.function_suggestion(&signature.name, &signature.module, caller_module);
+                return Err(format!("Function '{}' is not defined in the module '{}'{}", signature.name, module, suggestion))
             }
         }
 
-        // owner type
-        signatures.retain(|k| k.owner_type == signature.owner_type);
+        // visibility of the function
+        let candidates = signatures.clone();
+        signatures.retain(|k| k.module == *caller_module || self.functions.get(k).unwrap().is_public());
         if signatures.is_empty() {
+            let module = candidates[0].module.clone().unwrap_or_default();
+            return Err(format!("Function '{}' is private to the module '{}'", signature.name, module))
+        }
+
+        Ok(signatures)
+    }
+
+
+
+    /// Return the signatures of the functions with the name, module and owner type of the call, which can be called
+    /// from the caller's module. Empty if the module of the call was not imported
+    fn find_functions_by_owner(&self, signature: &FunctionCallSignature, caller_module: &Option<String>) -> Vec<&FunctionSignature> {
+        // a call without module can use a function of any module
+        let modules: Vec<Option<String>> = match &signature.module {
+            Some(m) if !self.imported_modules.contains(m) => return Vec::new(),
+            Some(m) => vec![None, Some(m.clone())],
+            None => std::iter::once(None).chain(self.imported_modules.iter().cloned().map(Some)).collect()
+        };
+
+        // an owner type containing Type::Any is equal to types which cannot be listed, so the index is searched
+        let signatures: Vec<&FunctionSignature> = match &signature.owner_type {
+            Some(t) if t.contains_any() => self.function_index.iter()
+                .filter(|((m, n, o), _)| modules.contains(m) && *n == signature.name && o.as_ref() == Some(t))
+                .flat_map(|(_, v)| v)
+                .collect(),
+            _ => {
+                let owners: Vec<Option<Type>> = match &signature.owner_type {
+                    Some(t) => t.generalizations().into_iter().map(Some).collect(),
+                    None => vec![None]
+                };
+                modules.iter()
+                    .flat_map(|m| owners.iter().map(move |o| (m.clone(), signature.name.clone(), o.clone())))
+                    .filter_map(|key| self.function_index.get(&key))
+                    .flatten()
+                    .collect()
+            }
+        };
+
+        signatures.into_iter()
+            .filter(|k| k.module == *caller_module || self.functions.get(k).unwrap().is_public())
+            .collect()
+    }
+
+
+
+    /// Return a suggestion of a function (from the given module, if any) with a name close to the given one,
+    /// or an empty string. Only the functions usable from the caller's module are suggested
+    fn function_suggestion(&self, name: &str, module: &Option<String>, caller_module: &Option<String>) -> String {
+        let visible: Vec<&FunctionSignature> = self.functions.iter()
+            .filter(|(k, f)| k.module == *caller_module || f.is_public())
+            .filter(|(k, _)| module.is_none() || k.module == *module)
+            .filter(|(k, _)| !k.name.starts_with('@') || name.starts_with('@'))
+            .map(|(k, _)| k)
+            .collect();
+
+        let closest = match suggestions::closest(name, visible.iter().map(|k| k.name.as_str())) {
+            Some(c) => c,
+            None => return String::new()
+        };
+
+        match visible.iter().find(|k| k.name == closest).and_then(|k| k.module.clone()) {
+            Some(m) if module.is_none() => format!(". Did you mean '{}' ({})?", closest, m),
+            _ => format!(". Did you mean '{}'?", closest)
+        }
+    }
+
+
+
+    /// Return an error if no function with the name and module of the call can be called from the caller's module
+    pub fn check_function_name(&self, signature: &FunctionCallSignature, caller_module: &Option<String>) -> Result<(), String> {
+        self.find_functions_by_name(signature, caller_module).map(|_| ())
+    }
+
+
+
+    /// Return the requested function definition.
+    /// Private functions are only returned if the caller is in the same module
+    pub fn get_function(&self, signature: &FunctionCallSignature, caller_module: &Option<String>) -> Result<&Box<dyn SlothFunction>, String> {
+        let mut signatures = self.find_functions_by_owner(signature, caller_module);
+
+        // owner type. If no function matches, the name, module and visibility are checked first
+        // to return a fitting error msg
+        if signatures.is_empty() {
+            self.find_functions_by_name(signature, caller_module)?;
             return match &signature.owner_type {
                 Some(t) => Err(format!("Function '{}' is not defined for the type {}", signature.name, t)),
                 None => Err(format!("Function '{}' is not defined", signature.name))
@@ -135,6 +266,7 @@ impl SlothProgram {
         }
 
         // input types
+        let overloads = signatures.clone();
         signatures.retain(
             |k| {
                 match &k.input_types {
@@ -148,7 +280,8 @@ impl SlothProgram {
         );
         if signatures.is_empty() {
             let type_str = signature.input_types.iter().map(|t| t.to_string()).collect::<Vec<String>>().join(", ");
-            return Err(format!("Function '{}' is not defined for the following input types: {}", signature.name, type_str))
+            let definitions = overloads.iter().map(|k| format!("'{}'", k.to_definition_string())).collect::<Vec<String>>().join(", ");
+            return Err(format!("Function '{}' is not defined for the following input types: {}\nNote: Available definitions: {}", signature.name, type_str, definitions))
         }
 
         // output type
@@ -175,6 +308,33 @@ impl SlothProgram {
 
 
 
+    /// Return the signatures of every function of the program
+    pub fn get_function_signatures(&self) -> Vec<&FunctionSignature> {
+        self.functions.keys().collect()
+    }
+
+
+
+    /// Return every structure of the program defined in Sloth
+    pub fn get_custom_structures(&self) -> Vec<&CustomDefinition> {
+        self.structures.values()
+            .filter_map(|s| s.as_ref().as_any_ref().downcast_ref::<CustomDefinition>())
+            .collect()
+    }
+
+
+
+    /// Return every function of the program written in Sloth
+    pub fn get_custom_functions(&self) -> Vec<&CustomFunction> {
+        self.functions.values()
+            .filter_map(|f| f.as_ref().as_any_ref().downcast_ref::<CustomFunction>())
+            .collect()
+    }
+
+
+
+
+
     /// Return the 'main' function of the program.
     /// Raise an error if there is 0 or more than 1 'main' functions
     pub fn get_main(&self) -> Result<&Box<dyn SlothFunction>, String> {
@@ -222,19 +382,41 @@ impl SlothProgram {
 
 
 
-    /// Return the blueprint of the given object name
-    pub fn get_struct(&self, signature: &StructSignature) -> Result<Box<dyn ObjectBlueprint>, String> {
+    /// Return the blueprint of the given object name.
+    /// Private structures are only returned if the caller is in the same module
+    pub fn get_struct(&self, signature: &StructSignature, caller_module: &Option<String>) -> Result<Box<dyn ObjectBlueprint>, String> {
+        let blueprint = self.find_struct(signature, caller_module)?;
+
+        if !blueprint.is_public() && blueprint.get_signature().module != *caller_module {
+            let module = blueprint.get_signature().module.unwrap_or_default();
+            return Err(format!("Structure '{}' is private to the module '{}'", signature.name, module))
+        }
+
+        Ok(blueprint)
+    }
+
+
+
+    /// Return the blueprint of the given object name, whatever its visibility
+    fn find_struct(&self, signature: &StructSignature, caller_module: &Option<String>) -> Result<Box<dyn ObjectBlueprint>, String> {
 
         // Check that if the potentially specified module exists
         match &signature.module {
             Some(n) => {
                 if !self.imported_modules.contains(n) {
-                    return Err(format!("Unknown module '{}'", n))
+                    let suggestion = suggestions::did_you_mean(n, self.imported_modules.iter().map(|m| m.as_str()));
+                    return Err(format!("Unknown module '{}'{}", n, suggestion))
                 }
             },
             None => ()
         };
 
+        // Without module, a structure of the caller's module is preferred
+        if signature.module.is_none() {
+            let same_module = StructSignature::new(caller_module.clone(), signature.name.clone());
+            if let Some(v) = self.structures.get(&same_module) {return Ok(v.box_clone())}
+        }
+
         // A perfect fit is found
         match self.structures.get(signature) {
             None => (),
@@ -243,7 +425,8 @@ impl SlothProgram {
 
         match &signature.module {
             Some(m) => {
-                return Err(format!("Structure '{}' does not exists in module '{}'", signature.name, m))
+                let names = self.structures.iter().filter(|(k, v)| k.module.as_ref() == Some(m) && (k.module == *caller_module || v.is_public())).map(|(k, _)| k.name.as_str());
+                Err(format!("Structure '{}' does not exists in module '{}'{}", signature.name, m, suggestions::did_you_mean(&signature.name, names)))
             },
 
             None => {
@@ -255,7 +438,10 @@ impl SlothProgram {
 
                 match matching_def.len() {
                     1 => return Ok(matching_def[0].box_clone()),
-                    0 => return Err(format!("Structure '{}' does not exists", signature.name)),
+                    0 => {
+                        let names = self.structures.iter().filter(|(k, v)| k.module == *caller_module || v.is_public()).map(|(k, _)| k.name.as_str());
+                        Err(format!("Structure '{}' does not exists{}", signature.name, suggestions::did_you_mean(&signature.name, names)))
+                    },
                     n => return Err(format!("{} instances of structure '{}' found in the scope. Precise the module like that: module:StructureName {{...}}", n, signature.name))
                 }
             }
@@ -267,6 +453,48 @@ impl SlothProgram {
 
 
 
+    /// Give their module to the structure types written without one (ex: 'Point' instead of 'geo:Point') in the custom
+    /// functions and structures. Such a type refers to the structure of the same module, else to the one of the main file,
+    /// else to the only structure with this name. Types which can't be resolved are left as is
+    pub fn resolve_types(&mut self) {
+        let known: Vec<StructSignature> = self.structures.keys().cloned().collect();
+
+        for blueprint in self.structures.values_mut() {
+            if let Some(definition) = blueprint.as_mut().as_any().downcast_mut::<CustomDefinition>() {
+                let module = definition.signature.module.clone();
+                for (_, field_type) in definition.fields.iter_mut() {
+                    *field_type = resolve_type(field_type, &module, &known);
+                }
+            }
+        }
+
+        // the signatures are the keys of the function map, so it is rebuilt with its index
+        let functions = std::mem::take(&mut self.functions);
+        self.function_index.clear();
+        self.functions_version += 1;
+        for (_, mut function) in functions {
+            if let Some(custom) = function.as_mut().as_any().downcast_mut::<CustomFunction>() {
+                let module = custom.signature.module.clone();
+                let signature = &mut custom.signature;
+
+                signature.owner_type = signature.owner_type.as_ref().map(|t| resolve_type(t, &module, &known));
+                signature.output_type = signature.output_type.as_ref().map(|t| resolve_type(t, &module, &known));
+                if let Some(inputs) = &mut signature.input_types {
+                    for (input_type, _) in inputs.iter_mut() {
+                        *input_type = resolve_type(input_type, &module, &known);
+                    }
+                }
+            }
+            let signature = function.get_signature();
+            self.function_index.entry(function_key(&signature)).or_default().push(signature.clone());
+            self.functions.insert(signature, function);
+        }
+    }
+
+
+
+
+
     /// Add an expression to the statics, return error if the name is already used
     pub fn push_static(&mut self, name: &String, expr: Rc<Expression>) -> Result<(), String> {
         match self.statics.insert(name.clone(), expr) {
@@ -293,6 +521,62 @@ impl SlothProgram {
 
 
 
+    /// Store a structure type written in the given module, so its existence can be checked once the program is built
+    pub fn push_type_annotation(&mut self, signature: StructSignature, module: Option<String>, position: Position) {
+        self.type_annotations.push((signature, module, position))
+    }
+
+
+
+    /// Return every structure type written in the source, with its module and position
+    pub fn get_type_annotations(&self) -> &Vec<(StructSignature, Option<String>, Position)> {
+        &self.type_annotations
+    }
+
+
+
+    /// Set the number of errors after which the parsing stops (at least 1)
+    pub fn set_max_errors(&mut self, max_errors: usize) {
+        self.max_errors = max_errors.max(1);
+    }
+
+
+
+    /// Store an error the parser recovered from. Ignored once the maximum number of errors is reached
+    pub fn push_parse_error(&mut self, error: Error) {
+        if !self.too_many_parse_errors() {self.parse_errors.push(error)}
+    }
+
+
+
+    /// Return whether the maximum number of parsing errors is reached, in which case the parsing must stop
+    pub fn too_many_parse_errors(&self) -> bool {
+        self.parse_errors.len() >= self.max_errors
+    }
+
+
+
+    /// Return the errors found while parsing, emptying the list
+    pub fn take_parse_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.parse_errors)
+    }
+
+
+
+    /// Return the name of every static of the program
+    pub fn get_static_names(&self) -> Vec<String> {
+        self.statics.keys().cloned().collect()
+    }
+
+
+
+    /// Return the expression of the given static, without evaluating it
+    pub fn get_static_expr(&self, name: &String) -> Option<Rc<Expression>> {
+        self.statics.get(name).cloned()
+    }
+
+
+
     /// Return whether the given static is set or not
     pub fn is_set(&self, name: &String) -> bool {
         self.statics.contains_key(name)
@@ -317,6 +601,27 @@ impl SlothProgram {
 
 
 
+    /// Register a package which can then be imported by its name
+    pub fn register_package(&mut self, name: String, entry: PathBuf) {
+        self.packages.insert(name, entry);
+    }
+
+
+    /// Return the entry file of the given package, or None if no package has this name
+    pub fn get_package(&self, name: &String) -> Option<PathBuf> {
+        self.packages.get(name).cloned()
+    }
+
+
+    /// Mark the given package as loaded. Return false if it was already loaded
+    pub fn load_package(&mut self, name: &String) -> bool {
+        if self.loaded_packages.contains(name) {return false}
+        self.loaded_packages.push(name.clone());
+        true
+    }
+
+
+
     /// Import the requested builtins
     pub fn import_builtins(&mut self) -> Result<(), String> {
         let (f, s) = builtins::collapse_imports(self.builtins.clone())?;
@@ -390,4 +695,37 @@ impl SlothProgram {
             println!("{:25}{:15}{:15}{:25}{:15}", signature.name, module_txt, type_txt, input_types_txt, output_type_str);
         }
     }
-}
\ No newline at end of file
+}
+
+
+
+
+
+
+/// Resolve the module of the structure types written without one, from the given module (see [SlothProgram::resolve_types])
+fn resolve_type(t: &Type, module: &Option<String>, known: &[StructSignature]) -> Type {
+    match t {
+        Type::List(inner) => Type::List(Box::new(resolve_type(inner, module, known))),
+        Type::Object(signature) if signature.module.is_none() => {
+            let same_module = StructSignature::new(module.clone(), signature.name.clone());
+            if known.contains(&same_module) {return Type::Object(same_module)}
+            if known.contains(signature) {return t.clone()}
+
+            let matching: Vec<&StructSignature> = known.iter().filter(|s| s.name == signature.name).collect();
+            match matching.len() {
+                1 => Type::Object(matching[0].clone()),
+                _ => t.clone()
+            }
+        },
+        _ => t.clone()
+    }
+}
+
+
+
+
+
+/// Return the key of the function in the function index
+fn function_key(signature: &FunctionSignature) -> FunctionKey {
+    (signature.module.clone(), signature.name.clone(), signature.owner_type.clone())
+}
@@ -0,0 +1,34 @@
+//! Resolution of the variables of a function before it runs. Each variable assigned in the function is a local
+//! variable and gets a slot in its scope (see [super::scope::Layout]); the other names can only be statics.
+//! As a variable is created by its first assignment, reading a local variable before it still falls back
+//! to the static of the same name.
+
+use super::expression::Expression;
+use super::scope::Layout;
+use super::statement::Statement;
+
+
+
+/// Return the layout of the scope of a function with the given number of inputs and statements
+pub fn resolve(inputs: usize, statements: &[Statement]) -> Layout {
+    let mut locals = Vec::new();
+    let reserved = Layout::new(inputs, Vec::new());
+    for statement in statements {find_locals(statement, &reserved, &mut locals)}
+    Layout::new(inputs, locals)
+}
+
+
+/// Add the variables assigned in the statement which are not reserved (@return, @self or an input) or already found
+fn find_locals(statement: &Statement, reserved: &Layout, locals: &mut Vec<String>) {
+    match statement {
+        Statement::Assignment(target, _, _) => {
+            if let Expression::VariableAccess(None, name, _) = target.as_ref() {
+                if reserved.get_slot(name).is_none() && !locals.contains(name) {locals.push(name.clone())}
+            }
+        },
+        Statement::If(_, statements, _) | Statement::While(_, statements, _) => {
+            for s in statements {find_locals(s, reserved, locals)}
+        },
+        Statement::ExpressionCall(..) => ()
+    }
+}
@@ -1,38 +1,122 @@
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::rc::Rc;
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
+use crate::suggestions;
 
 use super::program::SlothProgram;
 use super::value::Value;
 
 
+pub const RETURN_SLOT: usize = 0;
+pub const SELF_SLOT: usize = 1;
+const FIRST_INPUT_SLOT: usize = 2;
+
+
+
+#[derive(Debug, Clone, Default)]
+/// Names of the variables of a scope, by slot: '@return', '@self', the inputs ('@0', '@1', etc.), then the local variables.
+/// The local variables of a function are known before it runs (see [super::resolver]), so it is shared by its calls
+pub struct Layout {
+    pub inputs: usize,              // number of inputs
+    pub locals: Rc<Vec<String>>,    // names of the local variables
+}
+
+
+impl Layout {
+    pub fn new(inputs: usize, locals: Vec<String>) -> Layout {
+        Layout {inputs, locals: Rc::new(locals)}
+    }
+
+
+    /// Return the number of slots
+    pub fn slots_count(&self) -> usize {
+        FIRST_INPUT_SLOT + self.inputs + self.locals.len()
+    }
+
+
+    /// Return the slot of the input of the given index
+    pub fn input_slot(index: usize) -> usize {
+        FIRST_INPUT_SLOT + index
+    }
+
+
+    /// Return the slot of the variable with the given name, if any
+    pub fn get_slot(&self, name: &str) -> Option<usize> {
+        match name {
+            "@return" => Some(RETURN_SLOT),
+            "@self" => Some(SELF_SLOT),
+            _ => {
+                if let Some(i) = name.strip_prefix('@').and_then(|i| i.parse::<usize>().ok()) {
+                    if i < self.inputs {return Some(FIRST_INPUT_SLOT + i)}
+                }
+                self.locals.iter().position(|n| n == name).map(|i| FIRST_INPUT_SLOT + self.inputs + i)
+            }
+        }
+    }
+
+
+    /// Return the name of the variable in the given slot
+    pub fn get_name(&self, slot: usize) -> String {
+        match slot {
+            RETURN_SLOT => "@return".to_string(),
+            SELF_SLOT => "@self".to_string(),
+            _ if slot < FIRST_INPUT_SLOT + self.inputs => format!("@{}", slot - FIRST_INPUT_SLOT),
+            _ => self.locals[slot - FIRST_INPUT_SLOT - self.inputs].clone()
+        }
+    }
+}
+
+
+
+
 #[derive(Debug)]
 /// A scope is an environment in which variables lives.
 pub struct Scope {
-    pub variables: HashMap<String, Rc<RefCell<Value>>>,
+    layout: Layout,
+    slots: Vec<Option<Rc<RefCell<Value>>>>,     // value of each variable of the layout, if set
+    pub module: Option<String>,                 // module of the function running in this scope, used to check the visibility of definitions
 }
 
 
 impl Scope {
     pub fn new() -> Scope {
+        Scope::for_module(None)
+    }
+
+
+    /// Create an empty scope for a function of the given module
+    pub fn for_module(module: Option<String>) -> Scope {
+        Scope::with_layout(module, Layout::default())
+    }
+
+
+    /// Create a scope with the given variables, all unset, for a function of the given module
+    pub fn with_layout(module: Option<String>, layout: Layout) -> Scope {
         Scope {
-            variables: HashMap::new(),
+            slots: vec![None; layout.slots_count()],
+            layout,
+            module,
         }
     }
 
 
+    pub fn get_layout(&self) -> &Layout {
+        &self.layout
+    }
+
+
     /// Return the value contained in the given variable or static.
     /// Prefer local variable over global static
     pub fn get_variable(&self, name: String, program: &mut SlothProgram) -> Result<Rc<RefCell<Value>>, Error> {
-        match self.variables.get(&name) {
-            Some(v) => Ok(v.clone()),
+        match self.get_local(&name) {
+            Some(v) => Ok(v),
             None => {
                 match program.get_static(&name)? {
                     Some(v) => Ok(v),
                     None => {
-                        let err_msg = format!("Called uninitialised variable '{}'", name);
-                        Err(Error::new(ErrMsg::RuntimeError(err_msg), None))
+                        let err_msg = format!("Called uninitialised variable '{}'{}", name, self.variable_suggestion(&name, program));
+                        Err(Error::new(ErrMsg::RuntimeError(err_msg), None).with_code(ErrCode::S0401))
                     },
                 }
             }
@@ -40,22 +124,62 @@ impl Scope {
     }
 
 
+    /// Return the value of the local variable with the given name, if set
+    pub fn get_local(&self, name: &str) -> Option<Rc<RefCell<Value>>> {
+        self.layout.get_slot(name).and_then(|s| self.get_slot(s))
+    }
+
+
+    /// Return the value of the variable in the given slot, if set
+    pub fn get_slot(&self, slot: usize) -> Option<Rc<RefCell<Value>>> {
+        self.slots[slot].clone()
+    }
+
+
+    /// Set the value of the variable in the given slot
+    pub fn set_slot(&mut self, slot: usize, value: Rc<RefCell<Value>>) {
+        self.slots[slot] = Some(value);
+    }
+
+
+    /// Return the name and value of each variable set, in the order of their slots
+    pub fn get_variables(&self) -> Vec<(String, Rc<RefCell<Value>>)> {
+        self.slots.iter().enumerate()
+            .filter_map(|(i, v)| v.as_ref().map(|v| (self.layout.get_name(i), v.clone())))
+            .collect()
+    }
+
+
+    /// Return a suggestion of a variable or static with a name close to the given one, or an empty string
+    pub fn variable_suggestion(&self, name: &str, program: &SlothProgram) -> String {
+        let names: Vec<String> = self.get_variables().into_iter().map(|(n, _)| n).chain(program.get_static_names()).collect();
+        suggestions::did_you_mean(name, names.iter().map(|n| n.as_str()))
+    }
+
+
     /// Return whether the given variable is set or not
     pub fn is_set(&self, name: &String) -> bool {
-        self.variables.contains_key(name)
+        self.get_local(name).is_some()
     }
 
 
 
     /// Add a new variable to the scope with the given value. Fails if a value with the given id already exists
     pub fn push_variable(&mut self, name: String, value: Rc<RefCell<Value>>) -> Result<(), String> {
-        match self.variables.contains_key(&name) {
-            true => {
+        match self.layout.get_slot(&name) {
+            Some(s) if self.slots[s].is_some() => {
                 let error_msg = format!("Variable '{}' already exists", name);
                 Err(error_msg.to_string())
             },
-            false => {
-                self.variables.insert(name, value);
+            Some(s) => {
+                self.slots[s] = Some(value);
+                Ok(())
+            },
+
+            // a variable not known by the layout (ex: defined in the REPL) is added to it
+            None => {
+                Rc::make_mut(&mut self.layout.locals).push(name);
+                self.slots.push(Some(value));
                 Ok(())
             }
         }
@@ -66,20 +190,6 @@ impl Scope {
 
     /// Useful feature to get a list of each input values (@0, @1, @2, etc.), in order
     pub fn get_inputs(&self) -> Vec<Rc<RefCell<Value>>> {
-        let mut i = 0;
-        let mut res: Vec<Rc<RefCell<Value>>> = Vec::new();
-
-        loop {
-            let name = format!("@{}", i);
-            match self.variables.get(&name) {
-                Some(v) => {
-                    res.push(v.clone());
-                    i += 1;
-                }
-                None => break
-            }
-        }
-
-        res
+        self.slots[FIRST_INPUT_SLOT..FIRST_INPUT_SLOT + self.layout.inputs].iter().map_while(|v| v.clone()).collect()
     }
-}
\ No newline at end of file
+}
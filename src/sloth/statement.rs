@@ -1,9 +1,11 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::codes::ErrCode;
 use crate::errors::{Error, ErrMsg};
 use crate::position::Position;
 use super::expression::Expression;
+use super::hooks;
 use super::scope::Scope;
 use super::program::SlothProgram;
 use super::value::Value;
@@ -25,28 +27,14 @@ impl Statement {
 
     // Apply the statement to the given scope
     pub unsafe fn apply(&self, scope: Rc<RefCell<Scope>>, program: &mut SlothProgram) -> Result<(), Error> {
+        hooks::before_statement(&self.get_pos(), &scope, program);
+
         match self {
             Statement::Assignment(target, source, p) => {
                 // Get the reference to the source and target
                 let source_ref = source.evaluate(scope.clone(), program, false)?;
                 let target_ref = target.evaluate(scope.clone(), program, true)?;
-
-                // Compare the types, and if they match, assign the new value
-                let source_type = source_ref.borrow().get_type();
-                let target_type = target_ref.borrow().get_type();
-
-                if source_type != target_type {
-                    let err_msg = format!("Expected a Value of type '{}', got type '{}' instead", target_type, source_type);
-                    return Err(Error::new(ErrMsg::TypeError(err_msg), Some(p.clone())))
-                }
-
-                // Replace the value
-                match target_ref.try_borrow_mut() {
-                    Ok(mut borrow) => *borrow = source_ref.borrow().to_owned(),
-                    Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), Some(p.clone())))
-                }
-
-                Ok(())
+                Statement::assign(&source_ref, &target_ref, p)
             },
 
             Statement::ExpressionCall(expr, _) => {
@@ -61,7 +49,7 @@ impl Statement {
                         for statement in statements {statement.apply(scope.clone(), program)?}
                     }
                     Value::Boolean(false) => {},
-                    _ => {return Err(Error::new(ErrMsg::UnexpectedExpression("Expected boolean expression as 'if' condition".to_string()), Some(p.clone())))}
+                    _ => {return Err(Error::new(ErrMsg::UnexpectedExpression("Expected boolean expression as 'if' condition".to_string()), Some(p.clone())).with_code(ErrCode::S0503))}
                 }
 
                 Ok(())
@@ -81,6 +69,27 @@ impl Statement {
     }
 
 
+    /// Replace the value of the target by a copy of the source, if they are of the same type
+    pub fn assign(source_ref: &Rc<RefCell<Value>>, target_ref: &Rc<RefCell<Value>>, p: &Position) -> Result<(), Error> {
+        // Compare the types, and if they match, assign the new value
+        let source_type = source_ref.borrow().get_type();
+        let target_type = target_ref.borrow().get_type();
+
+        if source_type != target_type {
+            let err_msg = format!("Expected a Value of type '{}', got type '{}' instead", target_type, source_type);
+            return Err(Error::new(ErrMsg::TypeError(err_msg), Some(p.clone())).with_code(ErrCode::S0501))
+        }
+
+        // Replace the value
+        match target_ref.try_borrow_mut() {
+            Ok(mut borrow) => *borrow = source_ref.borrow().to_owned(),
+            Err(e) => return Err(Error::new(ErrMsg::RustError(e.to_string()), Some(p.clone())).with_code(ErrCode::S0901))
+        }
+
+        Ok(())
+    }
+
+
     pub fn get_pos(&self) -> Position {
         match self {
             Statement::Assignment(_, _, p) => p.clone(),
@@ -7,13 +7,14 @@ use std::iter::zip;
 
 use crate::sloth::types::Type;
 use crate::sloth::value::Value;
+use crate::suggestions;
 
 use super::value::DeepClone;
 
 
 
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct StructSignature {
     pub module: Option<String>,
     pub name: String
@@ -31,9 +32,10 @@ impl StructSignature {
 
 
 /// Trait stored in the Program. Used to build SlothObject
-pub trait ObjectBlueprint: Debug {
+pub trait ObjectBlueprint: Debug + ObjectToAny {
     fn box_clone(&self) -> Box<dyn ObjectBlueprint>;
     fn get_signature(&self) -> StructSignature;
+    fn is_public(&self) -> bool;
     fn build(&self, given_values: Vec<Rc<RefCell<Value>>>) -> Result<Box<dyn SlothObject>, String>; // TODO: Change the Err from String to errors::Error to allow the builder to return any error msg
 }
 
@@ -49,11 +51,13 @@ pub trait ObjectBlueprint: Debug {
 pub struct CustomDefinition {
     pub signature: StructSignature,
     pub fields: Vec<(String, Type)>,
+    pub public: bool,                   // whether the structure can be used from other modules
+    pub private_fields: Vec<String>,    // fields only accessible from the module of the structure
 }
 
 impl CustomDefinition {
     pub fn new(signature: StructSignature, fields: Vec<(String, Type)>) -> CustomDefinition {
-        CustomDefinition { signature, fields }
+        CustomDefinition { signature, fields, public: true, private_fields: Vec::new() }
     }
 }
 
@@ -66,6 +70,10 @@ impl ObjectBlueprint for CustomDefinition {
         self.signature.clone()
     }
 
+    fn is_public(&self) -> bool {
+        self.public
+    }
+
     fn build(&self, given_values: Vec<Rc<RefCell<Value>>>) -> Result<Box<dyn SlothObject>, String> {
         // Compare lenght of given fields to the struct def
         if self.fields.len() != given_values.len() {
@@ -82,7 +90,7 @@ impl ObjectBlueprint for CustomDefinition {
                 if let Type::List(t_r) = &expected_type {
                     if let Type::List(t_g) = given_value.borrow().get_type() {
                         if (*t_g).strict_eq(&Type::Any) {
-                            Rc::new(RefCell::new(Value::List((**t_r).clone(), Vec::new())))
+                            Rc::new(RefCell::new(Value::List((**t_r).clone(), Rc::default())))
                         }
                         else {given_value.clone()}
                     }
@@ -116,6 +124,7 @@ impl ObjectBlueprint for CustomDefinition {
 /// Trait used to allow for downcasting Trait Objects into their corresponding structs
 pub trait ObjectToAny: 'static {
     fn as_any(&mut self) -> &mut dyn Any;
+    fn as_any_ref(&self) -> &dyn Any;
 }
 
 
@@ -123,6 +132,10 @@ impl<T: 'static> ObjectToAny for T {
     fn as_any(&mut self) -> &mut dyn Any {
         self
     }
+
+    fn as_any_ref(&self) -> &dyn Any {
+        self
+    }
 }
 
 
@@ -142,6 +155,8 @@ pub trait SlothObject: ObjectToAny + std::fmt::Display {
     fn get_blueprint(&self) -> Box<dyn ObjectBlueprint>;
     fn get_field(&self, field_name: &String) -> Result<Rc<RefCell<Value>>, String>;
     fn get_fields(&self) -> (Vec<String>, Vec<Rc<RefCell<Value>>>);
+    /// Return whether the field can be accessed from other modules than the one of the object
+    fn is_field_public(&self, field_name: &String) -> bool;
 
 
     /// Return a clone of the object, without reallocating its inner values
@@ -208,10 +223,17 @@ impl SlothObject for StructureObject {
     fn get_field(&self, field_name: &String) -> Result<Rc<RefCell<Value>>, String> {
         match self.fields.get(field_name) {
             Some(v) => Ok(v.clone()),
-            None => Err(format!("Structure '{}' does not have a field named '{}'", self.get_signature().name, field_name))
+            None => {
+                let suggestion = suggestions::did_you_mean(field_name, self.definition.fields.iter().map(|(n, _)| n.as_str()));
+                Err(format!("Structure '{}' does not have a field named '{}'{}", self.get_signature().name, field_name, suggestion))
+            }
         }
     }
 
+    fn is_field_public(&self, field_name: &String) -> bool {
+        !self.definition.private_fields.contains(field_name)
+    }
+
     fn get_fields(&self) -> (Vec<String>, Vec<Rc<RefCell<Value>>>) {
         let mut res = (Vec::new(), Vec::new());
 
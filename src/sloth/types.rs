@@ -1,4 +1,7 @@
+use std::rc::Rc;
+
 use super::value::Value;
+use super::structure::StructSignature;
 
 #[derive(Debug, Clone, Eq, PartialOrd, Ord, Hash)]
 pub enum Type {
@@ -7,7 +10,7 @@ pub enum Type {
     Number,
     String,
     List(Box<Type>),     // type of the list elements
-    Object(String)       // name of the string
+    Object(StructSignature)  // signature (module and name) of the structure
 }
 
 
@@ -18,8 +21,8 @@ impl Type {
             Type::Any => Value::Number(0.0), // Default value is a Number, should not cause problems as 'Any' type is only used in builtins, which should be ok with it
             Type::Boolean => Value::Boolean(false),
             Type::Number => Value::Number(0.0),
-            Type::String => Value::String("".to_string()),
-            Type::List(t) => Value::List(*t.clone(), Vec::new()),
+            Type::String => Value::from(String::new()),
+            Type::List(t) => Value::List(*t.clone(), Rc::default()),
             Type::Object(_s) => Value::Any, // TEMPORARY TODO
         }
     }
@@ -33,6 +36,38 @@ impl Type {
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
+
+
+    /// Return whether the type is or contains Type::Any
+    pub fn contains_any(&self) -> bool {
+        match self {
+            Type::Any => true,
+            Type::List(t) => t.contains_any(),
+            _ => false
+        }
+    }
+
+
+    /// Return whether the type is or contains a structure
+    pub fn contains_object(&self) -> bool {
+        match self {
+            Type::Object(_) => true,
+            Type::List(t) => t.contains_object(),
+            _ => false
+        }
+    }
+
+
+    /// Return the types equal to this one, if it does not contain Type::Any: itself and
+    /// the types where a part of it is replaced by Type::Any (ex: list[num], list[any] and any for list[num])
+    pub fn generalizations(&self) -> Vec<Type> {
+        let mut types = match self {
+            Type::List(t) => t.generalizations().into_iter().map(|g| Type::List(Box::new(g))).collect(),
+            _ => vec![self.clone()]
+        };
+        types.push(Type::Any);
+        types
+    }
 }
 
 
@@ -44,7 +79,10 @@ impl std::fmt::Display for Type {
             Type::Number => write!(f, "num"),
             Type::String => write!(f, "string"),
             Type::List(t) => write!(f, "list[{}]", t),
-            Type::Object(n) => write!(f, "{}", n),
+            Type::Object(s) => match &s.module {
+                Some(m) => write!(f, "{}:{}", m, s.name),
+                None => write!(f, "{}", s.name),
+            },
         }
     }
 }
@@ -19,12 +19,16 @@ pub trait DeepClone {
 
 
 //#[derive(Clone)]
+/// The strings and lists are shared by the copies of a value, so copying it is cheap. They are copied
+/// when modified while shared (with Rc::make_mut).
+/// Note: an element of a list is only modified through a reference which is not shared with the copies of the list
+/// (see Value::get_field_mut)
 pub enum Value {
     Any,
     Number(f64),
     Boolean(bool),
-    String(String),
-    List(Type, Vec<Rc<RefCell<Value>>>),
+    String(Rc<String>),
+    List(Type, Rc<Vec<Rc<RefCell<Value>>>>),
     Object(Box<dyn SlothObject>)
 }
 
@@ -74,7 +78,7 @@ impl DeepClone for Value {
                 let new_vec: Result<Vec<Rc<RefCell<Value>>>, String> = v.iter()
                                                         .map(|r| r.borrow().deep_clone())
                                                         .collect();
-                Value::List(t.clone(), new_vec?)
+                Value::List(t.clone(), Rc::new(new_vec?))
             },
             Self::Object(o) => Value::Object(o.deep_clone()?),
         };
@@ -106,7 +110,7 @@ impl From<f64> for Value {
     fn from(v: f64) -> Self {Value::Number(v)}
 }
 impl From<String> for Value {
-    fn from(v: String) -> Self {Value::String(v)}
+    fn from(v: String) -> Self {Value::String(Rc::new(v))}
 }
 impl From<bool> for Value {
     fn from(v: bool) -> Self {Value::Boolean(v)}
@@ -121,7 +125,7 @@ impl Into<f64> for Value {
 }
 impl Into<String> for Value {
     fn into(self) -> String {
-        if let Value::String(x) = self {x}
+        if let Value::String(x) = self {Rc::unwrap_or_clone(x)}
         else {panic!("Implicit conversion from Value to String failed")}
     }
 }
@@ -144,7 +148,7 @@ impl Value {
             Value::Boolean(_) => Type::Boolean,
             Value::String(_) => Type::String,
             Value::List(t, _) => Type::List(Box::new(t.clone())),
-            Value::Object(object) => Type::Object(object.get_signature().name)
+            Value::Object(object) => Type::Object(object.get_signature())
         }
     }
 
@@ -158,11 +162,11 @@ impl Value {
                 else {"false".to_string()}
             },
 
-            Value::String(s) => s.clone(),
+            Value::String(s) => s.to_string(),
             
             Value::List(_, values) => {
                 let mut string_vec: Vec<String> = Vec::new();
-                for v in values {
+                for v in values.iter() {
                     let borrow = v.borrow();
                     if borrow.get_type() == Type::String {string_vec.push(format!("\"{}\"", borrow));}
                     else {string_vec.push(borrow.to_string())}
@@ -182,7 +186,7 @@ impl Value {
         else if s == "false" {Value::Boolean(false)}
         else if s.starts_with("\"") && s.ends_with("\"") {
             let text = s.trim_start_matches("\"").trim_end_matches("\"").to_string();
-            Value::String(text)
+            Value::from(text)
         }
         else {panic!("Can't generate Value from string '{}'", s)}
     }
@@ -192,7 +196,7 @@ impl Value {
     pub fn string_to_value(s: String, t: Type) -> Result<Value, String> {
         match t {
             Type::Any => panic!("Cannot generate a value from type Unknown"),
-            Type::String => Ok(Value::String(s)),
+            Type::String => Ok(Value::from(s)),
             Type::Number => {
                 match s.parse::<f64>() {
                     Ok(v) => Ok(Value::Number(v)),
@@ -212,10 +216,17 @@ impl Value {
     }
 
 
-    /// Return a smart pointer to the field of the value
-    pub fn get_field(&self, field_name: &String) -> Result<Rc<RefCell<Value>>, String> {
+    /// Return a smart pointer to the field of the value.
+    /// Private fields of objects can only be accessed from the module defining their structure
+    pub fn get_field(&self, field_name: &String, caller_module: &Option<String>) -> Result<Rc<RefCell<Value>>, String> {
         match self {
-            Value::Object(object) => object.get_field(field_name),
+            Value::Object(object) => {
+                let signature = object.get_signature();
+                if signature.module != *caller_module && !object.is_field_public(field_name) {
+                    return Err(format!("Field '{}' of structure '{}' is private to the module '{}'", field_name, signature.name, signature.module.unwrap_or_default()))
+                }
+                object.get_field(field_name)
+            },
 
             Value::List(_, list_values) => {
                 match field_name.parse::<usize>() {
@@ -232,6 +243,29 @@ impl Value {
             v => Err(format!("Type '{}' doesn't have a field '{}'", v.get_type(), field_name))
         }
     }
+
+
+    /// Return the reference to the field of the value (an element for a list), to modify it.
+    /// The element of a list is not shared with the copies of the list: the list and the element are copied if needed
+    pub fn get_field_mut(&mut self, field_name: &String, caller_module: &Option<String>) -> Result<Rc<RefCell<Value>>, String> {
+        match self {
+            Value::List(_, list_values) => {
+                let index = match field_name.parse::<usize>() {
+                    Ok(i) if i < list_values.len() => i,
+                    _ => return self.get_field(field_name, caller_module)
+                };
+
+                let element = &mut Rc::make_mut(list_values)[index];
+                if Rc::strong_count(element) > 1 {
+                    let value = element.borrow().clone();
+                    *element = Rc::new(RefCell::new(value));
+                }
+                Ok(element.clone())
+            },
+
+            _ => self.get_field(field_name, caller_module)
+        }
+    }
 }
 
 
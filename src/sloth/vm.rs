@@ -0,0 +1,136 @@
+//! Stack machine running the bytecode of the functions (see [super::bytecode]).
+//! The values are references, like the results of the evaluation of expressions. The variables, fields,
+//! function calls and assignments share their implementation with the evaluation of the tree, so both
+//! behave (and fail) the same way.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::codes::ErrCode;
+use crate::errors::{Error, ErrMsg};
+use crate::position::Position;
+use super::bytecode::{Chunk, Instruction};
+use super::expression::Expression;
+use super::hooks;
+use super::program::SlothProgram;
+use super::scope::Scope;
+use super::statement::Statement;
+use super::types::Type;
+use super::value::Value;
+
+
+
+impl Chunk {
+    /// Run the bytecode in the scope of the function
+    pub unsafe fn run(&self, scope: Rc<RefCell<Scope>>, program: *mut SlothProgram) -> Result<(), Error> {
+        let mut stack: Vec<Rc<RefCell<Value>>> = Vec::new();
+        let mut pc = 0;
+
+        while let Some((instruction, context)) = self.instructions.get(pc) {
+            pc += 1;
+
+            let positions = &self.contexts[*context];
+            if let Err(e) = self.execute(instruction, &positions[0], &mut stack, &mut pc, &scope, program) {
+                // the positions of the expressions containing the instruction are added, the innermost first
+                return Err(positions.iter().fold(e, |e, p| e.with(p)))
+            }
+        }
+
+        Ok(())
+    }
+
+
+    /// Execute the instruction, at the given position in the source
+    unsafe fn execute(&self, instruction: &Instruction, p: &Position, stack: &mut Vec<Rc<RefCell<Value>>>, pc: &mut usize, scope: &Rc<RefCell<Scope>>, program: *mut SlothProgram) -> Result<(), Error> {
+        match instruction {
+            Instruction::Statement => hooks::before_statement(p, scope, program.as_mut().unwrap()),
+
+            Instruction::Literal(v) => stack.push(Rc::new(RefCell::new(v.clone()))),
+
+            Instruction::List(n) => {
+                let values = stack.split_off(stack.len() - n);
+                let list_type = match values.first() {
+                    Some(v) => v.borrow().get_type(),
+                    None => Type::Any
+                };
+                stack.push(Rc::new(RefCell::new(Value::List(list_type, Rc::new(values)))))
+            },
+
+            Instruction::CheckListElement(n) => {
+                let list_type = stack[stack.len() - 1 - n].borrow().get_type();
+                let value_type = stack[stack.len() - 1].borrow().get_type();
+
+                if value_type != list_type {
+                    let err_msg = format!("Created a list of type '{}' but this value is of type '{}'", list_type, value_type);
+                    return Err(Error::new(ErrMsg::InvalidArguments(err_msg), Some(p.clone())).with_code(ErrCode::S0502));
+                }
+            },
+
+            Instruction::Local(slot, for_assignment) => {
+                stack.push(Expression::access_slot(*slot, p, scope, program, *for_assignment)?)
+            },
+
+            Instruction::Static(name) => {
+                stack.push(Expression::access_variable(name, p, scope, program, false)?)
+            },
+
+            Instruction::Field(name, modified) => {
+                let owner = stack.pop().unwrap();
+                stack.push(Expression::access_field(&owner, name, p, scope, *modified)?)
+            },
+
+            Instruction::Index(modified) => {
+                let owner = stack.pop().unwrap();
+                let access = stack.pop().unwrap();
+                let access_str = access.borrow().to_string();
+                stack.push(Expression::access_field(&owner, &access_str, p, scope, *modified)?)
+            },
+
+            Instruction::Call(signature, cache, n, has_owner) => {
+                let owner = if *has_owner {stack.pop()} else {None};
+                let arguments = stack.split_off(stack.len() - n);
+                stack.push(Expression::call_function(signature, cache, owner, arguments, p, scope, program)?)
+            },
+
+            Instruction::New(signature, n) => {
+                let blueprint = match program.as_mut().unwrap().get_struct(signature, &scope.borrow().module) {
+                    Ok(v) => v,
+                    Err(e) => return Err(Error::new(ErrMsg::RuntimeError(e), Some(p.clone())).with_code(ErrCode::S0405))
+                };
+
+                let values = stack.split_off(stack.len() - n);
+                let object = match blueprint.build(values) {
+                    Ok(v) => v,
+                    Err(e) => return Err(Error::new(ErrMsg::InvalidArguments(e), Some(p.clone())).with_code(ErrCode::S0504))
+                };
+                stack.push(Rc::new(RefCell::new(Value::Object(object))))
+            },
+
+            Instruction::Assign => {
+                let target = stack.pop().unwrap();
+                let source = stack.pop().unwrap();
+                Statement::assign(&source, &target, p)?
+            },
+
+            Instruction::Pop => {stack.pop();},
+
+            Instruction::Jump(target) => *pc = *target,
+
+            Instruction::JumpIfNot(target, strict) => {
+                let value = stack.pop().unwrap();
+                let value = value.borrow();
+                match *value {
+                    Value::Boolean(true) => (),
+                    Value::Boolean(false) => *pc = *target,
+                    _ if *strict => {
+                        let err_msg = "Expected boolean expression as 'if' condition".to_string();
+                        return Err(Error::new(ErrMsg::UnexpectedExpression(err_msg), Some(p.clone())).with_code(ErrCode::S0503))
+                    },
+                    _ => *pc = *target
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Registry of the source texts read by the lexer, so the diagnostics can quote them.
+//! A source may not be on disk (standard input, inline code, etc.), or may have changed since it was read.
+//! The in-memory sources are read by the lexer from this registry instead of the filesystem, under a virtual filename.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+
+
+/// Virtual filename of the source read from the standard input
+pub const STDIN_NAME: &str = "<stdin>";
+
+/// Virtual filename of the source given with '-e'
+pub const INLINE_NAME: &str = "<inline>";
+
+
+
+struct Source {
+    text: Arc<str>,
+    in_memory: bool,        // not read from a file
+}
+
+
+fn registry() -> &'static Mutex<HashMap<String, Source>> {
+    static SOURCES: OnceLock<Mutex<HashMap<String, Source>>> = OnceLock::new();
+    SOURCES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+
+
+
+/// Register the text of the given source, replacing the previous one with the same name
+pub fn register(name: &str, text: &str) {
+    if let Ok(mut sources) = registry().lock() {
+        sources.insert(name.to_string(), Source {text: Arc::from(text), in_memory: false});
+    }
+}
+
+
+/// Register a source which is not on disk, under the given virtual filename.
+/// The lexer will read it from here instead of opening a file
+pub fn register_in_memory(name: &str, text: &str) {
+    if let Ok(mut sources) = registry().lock() {
+        sources.insert(name.to_string(), Source {text: Arc::from(text), in_memory: true});
+    }
+}
+
+
+/// Return the text of the in-memory source with the given name, or None if it was not registered with [register_in_memory]
+pub fn get_in_memory(name: &str) -> Option<Arc<str>> {
+    registry().lock().ok().and_then(|s| s.get(name).filter(|s| s.in_memory).map(|s| s.text.clone()))
+}
+
+
+/// Return the text of the given source: the registered one, or the content of the file with this name.
+/// Return None if the source is unknown
+pub fn get(name: &str) -> Option<Arc<str>> {
+    if let Some(text) = registry().lock().ok().and_then(|s| s.get(name).map(|s| s.text.clone())) {
+        return Some(text)
+    }
+
+    std::fs::read_to_string(name).ok().map(Arc::from)
+}
+
+
+/// Forget the source with the given name: the file will be read from the disk again
+pub fn remove(name: &str) {
+    if let Ok(mut sources) = registry().lock() {
+        sources.remove(name);
+    }
+}
+
+
+/// Return the names of every registered source
+pub fn get_names() -> Vec<String> {
+    match registry().lock() {
+        Ok(sources) => sources.keys().cloned().collect(),
+        Err(_) => Vec::new()
+    }
+}
@@ -0,0 +1,57 @@
+//! Suggestions of close names, added to the errors caused by a probably misspelled name.
+
+
+
+
+/// Return the edit distance between the two strings: the number of insertions, deletions, substitutions
+/// and transpositions of two adjacent chars needed to go from one to the other
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // d[i][j] is the distance between the first i chars of a and the first j chars of b
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {row[0] = i}
+    for (j, cell) in d[0].iter_mut().enumerate() {*cell = j}
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] {0} else {1};
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+
+
+
+/// Return the candidate the closest to the given name, if it is close enough to be a typo of it
+pub fn closest<'a, I: IntoIterator<Item = &'a str>>(name: &str, candidates: I) -> Option<&'a str> {
+    // the longer the name, the more typos are tolerated. A name starting with a candidate is also
+    // likely a typo of it (ex: 'lenght' for 'len')
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    candidates.into_iter()
+        .filter(|c| *c != name)
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|(d, c)| *d <= max_distance || (c.chars().count() >= 3 && name.starts_with(c)))
+        .min()
+        .map(|(_, c)| c)
+}
+
+
+
+
+/// Return ". Did you mean 'x'?", to be appended to an error message, or an empty string if no candidate is close
+pub fn did_you_mean<'a, I: IntoIterator<Item = &'a str>>(name: &str, candidates: I) -> String {
+    match closest(name, candidates) {
+        Some(c) => format!(". Did you mean '{}'?", c),
+        None => String::new()
+    }
+}
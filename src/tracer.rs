@@ -0,0 +1,110 @@
+//! Execution tracer. It follows the execution with an [ExecutionHook], logging each statement applied and each
+//! function call and return (with its arguments and returned value), indented by the depth of the calls.
+//! The trace can be limited to some functions or modules: an event is logged if the function executing the
+//! statement, or the called function, matches one of the filters.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::errors::{Error, Frame};
+use crate::repl::value_to_string;
+use crate::sloth::function::SlothFunction;
+use crate::sloth::hooks::ExecutionHook;
+use crate::sloth::program::SlothProgram;
+use crate::sloth::scope::{Scope, SELF_SLOT};
+use crate::sloth::value::Value;
+use crate::position::Position;
+use crate::sources;
+
+
+
+// indentation of each level of calls
+const INDENT: &str = "  ";
+
+
+
+pub struct Tracer {
+    output: Box<dyn Write>,
+    functions: Vec<String>,     // names of the functions traced (every function if empty)
+    modules: Vec<String>,       // modules of the functions traced (every module if empty)
+    stack: Vec<Frame>,          // function calls being executed, the deepest last
+}
+
+
+
+
+/// Return the short location of the position (ex: 'main.slo:12')
+fn location(position: &Position) -> String {
+    format!("{}:{}", position.filename, position.line + 1)
+}
+
+
+
+
+impl Tracer {
+    pub fn new(output: Box<dyn Write>, functions: Vec<String>, modules: Vec<String>) -> Tracer {
+        Tracer {output, functions, modules, stack: Vec::new()}
+    }
+
+
+    /// Return whether the events of the function are traced
+    fn is_traced(&self, frame: Option<&Frame>) -> bool {
+        let frame = match frame {
+            Some(f) => f,
+            None => return self.functions.is_empty() && self.modules.is_empty()
+        };
+
+        (self.functions.is_empty() || self.functions.contains(&frame.name))
+        && (self.modules.is_empty() || frame.module.as_ref().is_some_and(|m| self.modules.contains(m)))
+    }
+
+
+    /// Write a line of the trace at the given depth. The trace is best-effort: a failed write is ignored
+    fn write(&mut self, depth: usize, text: &str) {
+        let _ = writeln!(self.output, "{}{}", INDENT.repeat(depth), text);
+    }
+}
+
+
+
+
+impl ExecutionHook for Tracer {
+    fn before_statement(&mut self, position: &Position, _scope: &Rc<RefCell<Scope>>, _program: &mut SlothProgram) {
+        if !self.is_traced(self.stack.last()) {return}
+
+        // the first line of the statement, as written in the source
+        let text = sources::get(&position.filename)
+            .and_then(|s| s.lines().nth(position.line).map(|l| l.trim().to_string()))
+            .unwrap_or_default();
+
+        self.write(self.stack.len(), &format!("[{}] {}", location(position), text));
+    }
+
+
+    fn enter_function(&mut self, _function: &dyn SlothFunction, frame: &Frame, scope: &Rc<RefCell<Scope>>) {
+        if self.is_traced(Some(frame)) {
+            let scope = scope.borrow();
+            let mut arguments: Vec<String> = scope.get_inputs().iter().map(|v| value_to_string(&v.borrow())).collect();
+            if let Some(v) = scope.get_slot(SELF_SLOT) {arguments.insert(0, format!("@self = {}", value_to_string(&v.borrow())))}
+
+            let call_location = frame.call_position.as_ref().map(|p| format!(" at {}", location(p))).unwrap_or_default();
+            let text = format!("call {}({}){}", frame, arguments.join(", "), call_location);
+            self.write(self.stack.len(), &text);
+        }
+
+        self.stack.push(frame.clone());
+    }
+
+
+    fn exit_function(&mut self, _function: &dyn SlothFunction, frame: &Frame, result: &Result<Rc<RefCell<Value>>, Error>) {
+        self.stack.pop();
+        if !self.is_traced(Some(frame)) {return}
+
+        let text = match result {
+            Ok(v) => format!("return {} -> {}", frame, value_to_string(&v.borrow())),
+            Err(e) => format!("error in {}: {}", frame, e.message)
+        };
+        self.write(self.stack.len(), &text);
+    }
+}